@@ -1,3 +1,22 @@
+use std::path::Path;
+use std::process::Command;
+
 fn main() {
+    // 记录构建时的 git 提交，供 get_app_info 展示；不在 git 仓库中构建时省略
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=STICKY_GIT_COMMIT={}", commit);
+    }
+    for path in ["../.git/HEAD", "../.git/refs/heads"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+
     tauri_build::build()
 }
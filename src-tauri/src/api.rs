@@ -0,0 +1,143 @@
+use std::fs;
+use std::io::Read;
+
+use serde::Deserialize;
+use tauri::Manager;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::crypto::PassphraseState;
+use crate::error::AppError;
+
+// 本地 HTTP API 默认端口，可在 config.json 的 api_port 中修改（重启后生效）
+pub const DEFAULT_PORT: u16 = 27183;
+
+// 每个请求都必须携带该请求头，值为 app_data_dir/api_token 中的令牌
+pub const TOKEN_HEADER: &str = "X-Api-Token";
+
+const TOKEN_FILE: &str = "api_token";
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+#[derive(Deserialize)]
+struct NewNote {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    body: String,
+}
+
+// 读取令牌，首次使用时生成随机令牌并保存
+pub fn token(app_handle: &tauri::AppHandle) -> Result<String, AppError> {
+    let app_dir = crate::app_data_dir(app_handle)?;
+    let token_file = app_dir.join(TOKEN_FILE);
+    if let Ok(token) = fs::read_to_string(&token_file) {
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(token.to_string());
+        }
+    }
+
+    fs::create_dir_all(&app_dir).map_err(AppError::io("Failed to create app directory"))?;
+    let token = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    crate::write_atomic(&token_file, token.as_bytes())
+        .map_err(AppError::io("Failed to save API token"))?;
+    Ok(token)
+}
+
+// 只绑定 127.0.0.1，不对局域网开放
+pub fn start(app_handle: tauri::AppHandle) -> Result<(), AppError> {
+    let token = token(&app_handle)?;
+    let port = crate::config::load(&app_handle).api_port.unwrap_or(DEFAULT_PORT);
+    let server = Server::http(("127.0.0.1", port))
+        .map_err(AppError::platform("Failed to start HTTP API"))?;
+
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            handle(&app_handle, &token, request);
+        }
+    });
+    Ok(())
+}
+
+fn handle(app_handle: &tauri::AppHandle, token: &str, mut request: Request) {
+    let (status, body) = if authorized(&request, token) {
+        match route(app_handle, &mut request) {
+            Ok(response) => response,
+            Err(e) => (status_for(&e), serde_json::to_string(&e).unwrap_or_default()),
+        }
+    } else {
+        let e = AppError::InvalidInput(format!("Missing or invalid {} header", TOKEN_HEADER));
+        (401, serde_json::to_string(&e).unwrap_or_default())
+    };
+
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+    if let Err(e) = request.respond(response) {
+        tracing::warn!("Failed to respond to HTTP API request: {}", e);
+    }
+}
+
+fn route(app_handle: &tauri::AppHandle, request: &mut Request) -> Result<(u16, String), AppError> {
+    let method = request.method().clone();
+    let path = request.url().split('?').next().unwrap_or_default().to_string();
+    match (method, path.as_str()) {
+        (Method::Get, "/notes") => Ok((200, get_notes(app_handle)?)),
+        (Method::Post, "/notes") => Ok((201, post_note(app_handle, request)?)),
+        _ => Err(AppError::NotFound(format!("No such endpoint: {}", path))),
+    }
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(TOKEN_HEADER))
+        .is_some_and(|header| constant_time_eq(header.value.as_str().as_bytes(), token.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn status_for(error: &AppError) -> u16 {
+    match error {
+        AppError::NotFound(_) => 404,
+        AppError::InvalidInput(_) | AppError::Serialization(_) => 400,
+        AppError::Locked => 423,
+        AppError::StorageUnavailable(_) => 503,
+        AppError::StaleWrite { .. } => 409,
+        _ => 500,
+    }
+}
+
+// 返回与 load_notes 相同的笔记数组
+fn get_notes(app_handle: &tauri::AppHandle) -> Result<String, AppError> {
+    let app_dir = crate::storage_dir(app_handle)?;
+    let passphrase = app_handle.state::<PassphraseState>();
+    crate::read_notes_json(app_handle, &app_dir, &passphrase)
+}
+
+// 追加一条笔记，请求体为 {"title": "...", "body": "..."}，返回新建的笔记
+fn post_note(app_handle: &tauri::AppHandle, request: &mut Request) -> Result<String, AppError> {
+    crate::ensure_editable(app_handle)?;
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body)
+        .map_err(AppError::io("Failed to read request body"))?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err(AppError::InvalidInput("Request body is too large".to_string()));
+    }
+    let new_note: NewNote = serde_json::from_str(&body)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid note: {}", e)))?;
+    let passphrase = app_handle.state::<PassphraseState>();
+    let note = crate::insert_new_note(app_handle, &passphrase, new_note.title, new_note.body)?;
+    crate::watcher::notify_changed(app_handle, "notes.json");
+
+    serde_json::to_string(&note).map_err(AppError::serialization("Failed to serialize note"))
+}
@@ -0,0 +1,79 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::tasks::Task;
+
+pub const ARCHIVE_FILE: &str = "tasks_archive.json";
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchivedTask {
+    pub task: Task,
+    pub archived_at: String,
+}
+
+// 归档文件只追加不覆盖：每行一个 JSON 条目（JSON Lines），已有内容从不重写
+pub fn append(app_dir: &Path, tasks: Vec<Task>) -> Result<(), AppError> {
+    fs::create_dir_all(app_dir).map_err(AppError::io("Failed to create app directory"))?;
+    let archived_at = crate::timestamp::now_iso();
+    let mut lines = String::new();
+    for task in tasks {
+        let entry = ArchivedTask {
+            task,
+            archived_at: archived_at.clone(),
+        };
+        let line = serde_json::to_string(&entry)
+            .map_err(AppError::serialization("Failed to serialize archived task"))?;
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(app_dir.join(ARCHIVE_FILE))
+        .map_err(AppError::io("Failed to open task archive"))?;
+    // 上次追加中途崩溃时文件末尾没有换行，先补上，避免新条目接在写了一半的行后面
+    if ends_mid_line(&mut file).map_err(AppError::io("Failed to read task archive"))? {
+        lines.insert(0, '\n');
+    }
+    file.write_all(lines.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(AppError::io("Failed to write task archive"))
+}
+
+fn ends_mid_line(file: &mut fs::File) -> io::Result<bool> {
+    if file.metadata()?.len() == 0 {
+        return Ok(false);
+    }
+    let mut last = [0u8];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    Ok(last[0] != b'\n')
+}
+
+// 按归档先后顺序返回；末尾写了一半的行（如写入中途崩溃）会被跳过
+pub fn load(app_dir: &Path) -> Result<Vec<ArchivedTask>, AppError> {
+    let archive_file = app_dir.join(ARCHIVE_FILE);
+    if !archive_file.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file = fs::File::open(&archive_file).map_err(AppError::io("Failed to load task archive"))?;
+    let mut archived = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(AppError::io("Failed to load task archive"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => archived.push(entry),
+            Err(e) => tracing::warn!("Skipping malformed archive entry: {}", e),
+        }
+    }
+    Ok(archived)
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+// 笔记附带的图片保存在 attachments/<id>/<uuid>.<ext>，笔记的 attachments 字段记录相对于数据目录的路径
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+// 单张图片的大小上限，附件用于随手的截图，不适合保存大文件
+pub const MAX_BYTES: usize = 5 * 1024 * 1024;
+
+const ALLOWED_EXTENSIONS: [&str; 3] = ["png", "jpg", "gif"];
+
+// 扩展名忽略大小写与开头的 .，jpeg 按 jpg 处理
+fn normalize_extension(ext: &str) -> Result<&'static str, AppError> {
+    let ext = ext.trim_start_matches('.').to_ascii_lowercase();
+    let ext = if ext == "jpeg" { "jpg".to_string() } else { ext };
+    ALLOWED_EXTENSIONS
+        .into_iter()
+        .find(|allowed| *allowed == ext)
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "Unsupported image type: {}, expected png, jpg or gif",
+                ext
+            ))
+        })
+}
+
+// 按文件头确认内容与扩展名一致，避免把任意文件当作图片保存
+fn matches_signature(bytes: &[u8], ext: &str) -> bool {
+    match ext {
+        "png" => bytes.starts_with(b"\x89PNG\r\n\x1a\n"),
+        "jpg" => bytes.starts_with(&[0xff, 0xd8, 0xff]),
+        "gif" => bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a"),
+        _ => false,
+    }
+}
+
+// id 直接用作目录名，只允许字母、数字、- 与 _
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_valid_file_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\'])
+}
+
+fn note_dir(app_dir: &Path, id: &str) -> Result<PathBuf, AppError> {
+    if !is_valid_id(id) {
+        return Err(AppError::InvalidInput(format!(
+            "Note id cannot be used as a directory name: {}",
+            id
+        )));
+    }
+    Ok(app_dir.join(ATTACHMENTS_DIR).join(id))
+}
+
+// 保存图片并返回相对路径（统一使用 /，与平台无关）
+pub fn save(app_dir: &Path, id: &str, bytes: &[u8], ext: &str) -> Result<String, AppError> {
+    let ext = normalize_extension(ext)?;
+    if bytes.is_empty() {
+        return Err(AppError::InvalidInput("Image is empty".to_string()));
+    }
+    if bytes.len() > MAX_BYTES {
+        return Err(AppError::InvalidInput(format!(
+            "Image is {} bytes, the limit is {} bytes",
+            bytes.len(),
+            MAX_BYTES
+        )));
+    }
+    if !matches_signature(bytes, ext) {
+        return Err(AppError::InvalidInput(format!("Image content is not a valid {} file", ext)));
+    }
+
+    let dir = note_dir(app_dir, id)?;
+    fs::create_dir_all(&dir).map_err(AppError::io("Failed to create attachment directory"))?;
+    let file_name = format!("{}.{}", uuid::Uuid::new_v4(), ext);
+    crate::write_atomic(&dir.join(&file_name), bytes)
+        .map_err(AppError::io("Failed to save attachment"))?;
+    Ok(format!("{}/{}/{}", ATTACHMENTS_DIR, id, file_name))
+}
+
+// 删除笔记的一个附件；path 必须是该笔记目录下的文件，防止借机删除数据目录外的文件。
+// 文件已不存在时视为成功，目录删空后一并删除
+pub fn remove(app_dir: &Path, id: &str, path: &str) -> Result<(), AppError> {
+    let dir = note_dir(app_dir, id)?;
+    let prefix = format!("{}/{}/", ATTACHMENTS_DIR, id);
+    let file_name = path
+        .strip_prefix(&prefix)
+        .filter(|name| is_valid_file_name(name))
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("Not an attachment of note {}: {}", id, path))
+        })?;
+
+    match fs::remove_file(dir.join(file_name)) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(AppError::io("Failed to remove attachment")(e)),
+    }
+    // 目录中还有其它附件时删除会失败，忽略即可
+    let _ = fs::remove_dir(&dir);
+    Ok(())
+}
+
+// 删除笔记的整个附件目录，目录不存在时视为成功
+pub fn remove_all(app_dir: &Path, id: &str) -> Result<(), AppError> {
+    let dir = note_dir(app_dir, id)?;
+    match fs::remove_dir_all(&dir) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(AppError::io("Failed to remove attachments")(e)),
+    }
+}
+
+// 是否为 save 生成的相对路径：attachments/<id>/<文件名>，id 与文件名都不含路径分隔符
+pub fn is_attachment_path(path: &str) -> bool {
+    let Some(rest) = path
+        .strip_prefix(ATTACHMENTS_DIR)
+        .and_then(|rest| rest.strip_prefix('/'))
+    else {
+        return false;
+    };
+    rest.split_once('/')
+        .is_some_and(|(id, name)| is_valid_id(id) && is_valid_file_name(name))
+}
+
+// 数据目录中全部附件的相对路径，供整体导出使用
+pub fn list(app_dir: &Path) -> Result<Vec<String>, AppError> {
+    let root = app_dir.join(ATTACHMENTS_DIR);
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths = Vec::new();
+    for dir in fs::read_dir(&root).map_err(AppError::io("Failed to list attachments"))? {
+        let dir = dir.map_err(AppError::io("Failed to list attachments"))?;
+        if !dir.path().is_dir() {
+            continue;
+        }
+        let id = dir.file_name().to_string_lossy().into_owned();
+        for file in fs::read_dir(dir.path()).map_err(AppError::io("Failed to list attachments"))? {
+            let file = file.map_err(AppError::io("Failed to list attachments"))?;
+            let path = format!("{}/{}/{}", ATTACHMENTS_DIR, id, file.file_name().to_string_lossy());
+            if file.path().is_file() && is_attachment_path(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
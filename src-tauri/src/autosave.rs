@@ -0,0 +1,52 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::error::AppError;
+
+// 两次调用之间静默超过该时长才真正写盘
+pub const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(500);
+
+// 等待写盘的最新笔记内容；generation 每次调用递增，用于判断期间是否有新调用
+#[derive(Default)]
+pub struct PendingNotes {
+    latest: Mutex<Option<String>>,
+    generation: AtomicU64,
+}
+
+pub fn schedule(app_handle: &tauri::AppHandle, notes: String) {
+    let pending = app_handle.state::<PendingNotes>();
+    *pending.latest.lock().unwrap() = Some(notes);
+    let generation = pending.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(DEBOUNCE_INTERVAL);
+        // 期间又有新调用时由最后一次调用负责写盘
+        let current = app_handle.state::<PendingNotes>().generation.load(Ordering::SeqCst);
+        if current == generation {
+            if let Err(e) = flush(&app_handle) {
+                tracing::warn!("Failed to save notes: {}", e);
+            }
+        }
+    });
+}
+
+// 丢弃尚未写盘的内容，用于随后会被直接保存覆盖的场景
+pub fn discard(app_handle: &tauri::AppHandle) {
+    app_handle.state::<PendingNotes>().latest.lock().unwrap().take();
+}
+
+// 立即写入尚未落盘的内容（若有）；排队期间开启了只读模式时丢弃该内容
+pub fn flush(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let notes = app_handle.state::<PendingNotes>().latest.lock().unwrap().take();
+    match notes {
+        Some(notes) => {
+            crate::ensure_editable(app_handle)?;
+            crate::persist_notes(app_handle, &notes)
+        }
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::error::AppError;
+
+// 默认保留的滚动备份数量
+pub const DEFAULT_RETENTION: usize = 20;
+// 保留数量的下限，避免设为 0 时删光所有备份
+pub const MIN_RETENTION: usize = 1;
+
+// 早于该时长的备份可由 compact 按月合并为 notes-archive-<YYYY-MM>.zip，归档内保留原文件名与内容
+const COMPACT_AGE_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+const ARCHIVE_PREFIX: &str = "notes-archive-";
+const ARCHIVE_SUFFIX: &str = ".zip";
+
+#[derive(Serialize)]
+pub struct BackupEntry {
+    pub filename: String,
+    pub timestamp: u64,
+    // 已合并到月度归档中的备份所在的归档文件名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<String>,
+}
+
+// 配置中的保留数量，未设置时为 DEFAULT_RETENTION
+pub fn retention(app_handle: &tauri::AppHandle) -> usize {
+    crate::config::load(app_handle)
+        .backup_retention
+        .map_or(DEFAULT_RETENTION, |count| (count as usize).max(MIN_RETENTION))
+}
+
+pub fn backups_dir(app_dir: &Path) -> PathBuf {
+    app_dir.join("backups")
+}
+
+// 备份文件名形如 notes-<unix-millis>.json.gz；早期版本未压缩的 notes-<unix-millis>.json 同样识别
+fn parse_timestamp(filename: &str) -> Option<u64> {
+    let stem = filename.strip_prefix("notes-")?;
+    stem.strip_suffix(".json.gz")
+        .or_else(|| stem.strip_suffix(".json"))?
+        .parse()
+        .ok()
+}
+
+pub fn is_backup_name(filename: &str) -> bool {
+    parse_timestamp(filename).is_some()
+}
+
+// 归档文件名形如 notes-archive-2024-05.zip
+pub fn is_archive_name(filename: &str) -> bool {
+    filename
+        .strip_prefix(ARCHIVE_PREFIX)
+        .and_then(|rest| rest.strip_suffix(ARCHIVE_SUFFIX))
+        .is_some_and(|month| {
+            chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").is_ok()
+        })
+}
+
+fn is_compressed(filename: &str) -> bool {
+    filename.ends_with(".gz")
+}
+
+// .gz 备份透明解压
+fn unpack(filename: &str, data: Vec<u8>) -> io::Result<Vec<u8>> {
+    if !is_compressed(filename) {
+        return Ok(data);
+    }
+    let mut contents = Vec::new();
+    GzDecoder::new(data.as_slice()).read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+fn pack(filename: &str, contents: &[u8]) -> io::Result<Vec<u8>> {
+    if !is_compressed(filename) {
+        return Ok(contents.to_vec());
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(contents)?;
+    encoder.finish()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+// 读取备份内容，.gz 备份透明解压
+fn read_backup(path: &Path) -> io::Result<Vec<u8>> {
+    unpack(&file_name(path), fs::read(path)?)
+}
+
+// 按文件名决定是否压缩后原子写入
+pub fn write_backup(path: &Path, contents: &[u8]) -> io::Result<()> {
+    crate::write_atomic(path, &pack(&file_name(path), contents)?)
+}
+
+fn archive_entry_names(path: &Path) -> io::Result<Vec<String>> {
+    let archive = ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+    Ok(archive.file_names().map(str::to_string).collect())
+}
+
+fn read_archive_entry(path: &Path, name: &str) -> io::Result<Vec<u8>> {
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+    let mut entry = archive.by_name(name).map_err(io::Error::other)?;
+    let mut data = Vec::new();
+    entry.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+// 读出归档中的全部文件（保持原始字节，.gz 不解压）
+fn read_archive(path: &Path) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(io::Error::other)?;
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        entries.push((entry.name().to_string(), data));
+    }
+    Ok(entries)
+}
+
+// 已压缩的 .gz 备份直接存储，其余（早期未压缩的备份）按 deflate 压缩；整体原子写入
+fn write_archive(path: &Path, entries: &[(String, Vec<u8>)]) -> io::Result<()> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in entries {
+        let method = if is_compressed(name) {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+        let options = SimpleFileOptions::default().compression_method(method);
+        zip.start_file(name.as_str(), options).map_err(io::Error::other)?;
+        zip.write_all(data)?;
+    }
+    let buffer = zip.finish().map_err(io::Error::other)?.into_inner();
+    crate::write_atomic(path, &buffer)
+}
+
+// 备份的原始字节（.gz 不解压），无论是单独的文件还是已合并到月度归档中
+pub fn read_raw(app_dir: &Path, entry: &BackupEntry) -> io::Result<Vec<u8>> {
+    let dir = backups_dir(app_dir);
+    match &entry.archive {
+        Some(archive) => read_archive_entry(&dir.join(archive), &entry.filename),
+        None => fs::read(dir.join(&entry.filename)),
+    }
+}
+
+fn read_entry(app_dir: &Path, entry: &BackupEntry) -> io::Result<Vec<u8>> {
+    unpack(&entry.filename, read_raw(app_dir, entry)?)
+}
+
+// 覆盖 notes.json 之前调用：把现有文件复制到 backups 目录，并裁剪到最近 retention 份
+pub fn snapshot_notes(app_dir: &Path, retention: usize) -> io::Result<()> {
+    let notes_file = app_dir.join("notes.json");
+    if !notes_file.exists() {
+        return Ok(());
+    }
+
+    let dir = backups_dir(app_dir);
+    fs::create_dir_all(&dir)?;
+    let contents = fs::read(&notes_file)?;
+    write_backup(&dir.join(format!("notes-{}.json.gz", crate::now_millis())), &contents)?;
+
+    prune(app_dir, retention)
+}
+
+// 按时间倒序（最新在前）列出所有备份，包括月度归档中的备份；无法读取的归档跳过
+pub fn list(app_dir: &Path) -> io::Result<Vec<BackupEntry>> {
+    let dir = backups_dir(app_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let filename = entry?.file_name().to_string_lossy().into_owned();
+        if let Some(timestamp) = parse_timestamp(&filename) {
+            entries.push(BackupEntry {
+                filename,
+                timestamp,
+                archive: None,
+            });
+        } else if is_archive_name(&filename) {
+            let names = match archive_entry_names(&dir.join(&filename)) {
+                Ok(names) => names,
+                Err(e) => {
+                    tracing::warn!("Failed to read backup archive {}: {}", filename, e);
+                    continue;
+                }
+            };
+            for name in names {
+                if let Some(timestamp) = parse_timestamp(&name) {
+                    entries.push(BackupEntry {
+                        filename: name,
+                        timestamp,
+                        archive: Some(filename.clone()),
+                    });
+                }
+            }
+        }
+    }
+    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(entries)
+}
+
+// 删除超出保留数量的旧备份，返回删除的数量；月度归档中的备份作为历史保留，不会被删除
+pub fn prune(app_dir: &Path, retention: usize) -> io::Result<usize> {
+    let dir = backups_dir(app_dir);
+    let stale = list(app_dir)?
+        .into_iter()
+        .skip(retention)
+        .filter(|entry| entry.archive.is_none());
+
+    let mut removed = 0;
+    for entry in stale {
+        fs::remove_file(dir.join(&entry.filename))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+// 按文件名读取 backups 目录或月度归档中的备份，只接受备份文件名，避免读取目录之外的文件
+fn read_named(app_dir: &Path, filename: &str) -> Result<Vec<u8>, AppError> {
+    if parse_timestamp(filename).is_none() {
+        return Err(AppError::InvalidInput(format!("Invalid backup name: {}", filename)));
+    }
+
+    let path = backups_dir(app_dir).join(filename);
+    if path.exists() {
+        return read_backup(&path).map_err(AppError::io("Failed to read backup"));
+    }
+    let entry = list(app_dir)
+        .map_err(AppError::io("Failed to list backups"))?
+        .into_iter()
+        .find(|entry| entry.filename == filename)
+        .ok_or_else(|| AppError::NotFound(format!("Backup not found: {}", filename)))?;
+    read_entry(app_dir, &entry).map_err(AppError::io("Failed to read backup"))
+}
+
+// 读取备份中的笔记：解压、解密并升级到当前结构
+pub fn load_notes(
+    app_dir: &Path,
+    filename: &str,
+    passphrase: Option<&str>,
+) -> Result<Vec<crate::notes::Note>, AppError> {
+    let contents = crate::crypto::decode(read_named(app_dir, filename)?, passphrase)?;
+    crate::notes::parse(&crate::notes::from_envelope(&contents)?)
+}
+
+// 最近一份能解密并解析的备份：返回文件名与原始内容（已解压，加密的保持加密）
+pub fn latest_valid(app_dir: &Path, passphrase: Option<&str>) -> Option<(String, Vec<u8>)> {
+    list(app_dir).ok()?.into_iter().find_map(|entry| {
+        let data = read_entry(app_dir, &entry).ok()?;
+        let contents = crate::crypto::decode(data.clone(), passphrase).ok()?;
+        crate::notes::from_envelope(&contents).ok()?;
+        Some((entry.filename, data))
+    })
+}
+
+// 首次启用加密时调用：把已有的明文备份就地加密，避免敏感内容残留在磁盘上
+pub fn encrypt_all(app_dir: &Path, passphrase: &str) -> Result<(), AppError> {
+    let dir = backups_dir(app_dir);
+    let entries = list(app_dir).map_err(AppError::io("Failed to list backups"))?;
+    for entry in entries.iter().filter(|entry| entry.archive.is_none()) {
+        let path = dir.join(&entry.filename);
+        let data = read_backup(&path).map_err(AppError::io("Failed to read backup"))?;
+        if crate::crypto::is_encrypted(&data) {
+            continue;
+        }
+        let encrypted = crate::crypto::encrypt(passphrase, &data)?;
+        write_backup(&path, &encrypted).map_err(AppError::io("Failed to encrypt backup"))?;
+    }
+
+    let archives: BTreeSet<&String> =
+        entries.iter().filter_map(|entry| entry.archive.as_ref()).collect();
+    for archive in archives {
+        let path = dir.join(archive);
+        let mut files = read_archive(&path).map_err(AppError::io("Failed to read backup"))?;
+        let mut changed = false;
+        for (name, data) in files.iter_mut() {
+            let contents =
+                unpack(name, data.clone()).map_err(AppError::io("Failed to read backup"))?;
+            if crate::crypto::is_encrypted(&contents) {
+                continue;
+            }
+            let encrypted = crate::crypto::encrypt(passphrase, &contents)?;
+            *data = pack(name, &encrypted).map_err(AppError::io("Failed to encrypt backup"))?;
+            changed = true;
+        }
+        if changed {
+            write_archive(&path, &files).map_err(AppError::io("Failed to encrypt backup"))?;
+        }
+    }
+    Ok(())
+}
+
+fn month_of(timestamp: u64) -> Option<String> {
+    let time = chrono::DateTime::from_timestamp_millis(i64::try_from(timestamp).ok()?)?;
+    Some(time.with_timezone(&chrono::Local).format("%Y-%m").to_string())
+}
+
+// 把早于 COMPACT_AGE_MS 的备份按月份（本地时间）移入 notes-archive-<YYYY-MM>.zip，
+// 已有该月归档时合并进去；最近的备份保持单独的文件以便快速恢复。返回移入归档的备份数
+pub fn compact(app_dir: &Path) -> Result<usize, AppError> {
+    let dir = backups_dir(app_dir);
+    let cutoff = crate::now_millis().saturating_sub(COMPACT_AGE_MS);
+    let mut by_month: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for entry in list(app_dir).map_err(AppError::io("Failed to list backups"))? {
+        if entry.archive.is_some() || entry.timestamp >= cutoff {
+            continue;
+        }
+        if let Some(month) = month_of(entry.timestamp) {
+            by_month.entry(month).or_default().push(entry.filename);
+        }
+    }
+
+    let mut compacted = 0;
+    for (month, filenames) in by_month {
+        let path = dir.join(format!("{}{}{}", ARCHIVE_PREFIX, month, ARCHIVE_SUFFIX));
+        let mut files = if path.exists() {
+            read_archive(&path).map_err(AppError::io("Failed to read backup"))?
+        } else {
+            Vec::new()
+        };
+        for filename in &filenames {
+            if files.iter().any(|(name, _)| name == filename) {
+                continue;
+            }
+            let data = fs::read(dir.join(filename)).map_err(AppError::io("Failed to read backup"))?;
+            files.push((filename.clone(), data));
+        }
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        write_archive(&path, &files).map_err(AppError::io("Failed to write backup archive"))?;
+
+        // 归档写入成功后才删除原文件
+        for filename in filenames {
+            fs::remove_file(dir.join(&filename)).map_err(AppError::io("Failed to remove backup"))?;
+            compacted += 1;
+        }
+    }
+    Ok(compacted)
+}
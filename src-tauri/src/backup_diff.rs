@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::notes::Note;
+
+#[derive(Serialize)]
+pub struct TitleChange {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+}
+
+// 两份笔记数据按 id 比较的结果：a 为较早的一份，b 为较新的一份
+#[derive(Serialize, Default)]
+pub struct BackupDiff {
+    // 只在 b 中存在的笔记 id
+    pub added: Vec<String>,
+    // 只在 a 中存在的笔记 id
+    pub removed: Vec<String>,
+    // 两边都有但任一字段不同的笔记 id
+    pub modified: Vec<String>,
+    // modified 中标题发生变化的笔记
+    pub title_changes: Vec<TitleChange>,
+}
+
+pub fn diff(a: &[Note], b: &[Note]) -> BackupDiff {
+    let before: HashMap<&str, &Note> = a.iter().map(|note| (note.id.as_str(), note)).collect();
+    let after: HashMap<&str, &Note> = b.iter().map(|note| (note.id.as_str(), note)).collect();
+
+    let mut result = BackupDiff::default();
+    for note in b {
+        let Some(previous) = before.get(note.id.as_str()) else {
+            result.added.push(note.id.clone());
+            continue;
+        };
+        if serde_json::to_value(previous).ok() == serde_json::to_value(note).ok() {
+            continue;
+        }
+        result.modified.push(note.id.clone());
+        if previous.title != note.title {
+            result.title_changes.push(TitleChange {
+                id: note.id.clone(),
+                from: previous.title.clone(),
+                to: note.title.clone(),
+            });
+        }
+    }
+    result.removed = a
+        .iter()
+        .filter(|note| !after.contains_key(note.id.as_str()))
+        .map(|note| note.id.clone())
+        .collect();
+    result
+}
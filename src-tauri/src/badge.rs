@@ -0,0 +1,119 @@
+use tauri::Manager;
+
+use crate::error::AppError;
+
+// 3x5 点阵数字，用于绘制 Windows 覆盖图标；每行低 3 位从左到右表示像素
+#[cfg(target_os = "windows")]
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+#[cfg(target_os = "windows")]
+const OVERLAY_SIZE: u32 = 16;
+
+// 未完成的任务数
+pub fn incomplete_count(tasks: &serde_json::Value) -> usize {
+    tasks
+        .as_array()
+        .map(|tasks| {
+            tasks
+                .iter()
+                .filter(|task| task.get("completed").and_then(serde_json::Value::as_bool) != Some(true))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+// 重新读取任务并更新角标，返回未完成的任务数
+pub fn refresh(app_handle: &tauri::AppHandle) -> Result<usize, AppError> {
+    let app_dir = crate::storage_dir(app_handle)?;
+    let tasks = crate::tasks::parse(&crate::read_tasks_json(app_handle, &app_dir)?)?;
+    let count = tasks.iter().filter(|task| !task.completed).count();
+    apply(app_handle, count)?;
+    Ok(count)
+}
+
+// 托盘提示中显示数量；macOS 设置 Dock 角标，Windows 设置任务栏覆盖图标。数量为 0 时清除
+pub fn apply(app_handle: &tauri::AppHandle, count: usize) -> Result<(), AppError> {
+    if let Some(tray) = app_handle.tray_by_id("main") {
+        let tooltip = if count == 0 {
+            "任务清单".to_string()
+        } else {
+            format!("任务清单（{} 项未完成）", count)
+        };
+        tray.set_tooltip(Some(tooltip))?;
+    }
+
+    let Some(window) = app_handle.get_webview_window("main") else {
+        return Ok(());
+    };
+    #[cfg(target_os = "windows")]
+    window.set_overlay_icon((count > 0).then(|| overlay_icon(count)))?;
+    #[cfg(not(target_os = "windows"))]
+    window.set_badge_count((count > 0).then_some(count as i64))?;
+    Ok(())
+}
+
+// 红色圆形底上的白色数字，超过 99 时显示 99
+#[cfg(target_os = "windows")]
+fn overlay_icon(count: usize) -> tauri::image::Image<'static> {
+    let size = OVERLAY_SIZE;
+    let mut rgba = vec![0u8; (size * size * 4) as usize];
+    let mut set = |x: u32, y: u32, color: [u8; 4]| {
+        if x < size && y < size {
+            let offset = ((y * size + x) * 4) as usize;
+            rgba[offset..offset + 4].copy_from_slice(&color);
+        }
+    };
+
+    let center = (size as f32 - 1.0) / 2.0;
+    for y in 0..size {
+        for x in 0..size {
+            let (dx, dy) = (x as f32 - center, y as f32 - center);
+            if dx * dx + dy * dy <= center * center + 1.0 {
+                set(x, y, [0xd3, 0x2f, 0x2f, 0xff]);
+            }
+        }
+    }
+
+    let digits: Vec<usize> = count
+        .min(99)
+        .to_string()
+        .bytes()
+        .map(|digit| usize::from(digit - b'0'))
+        .collect();
+    // 一位数放大两倍绘制，两位数按原尺寸并排，中间留一列空隙
+    let scale = if digits.len() == 1 { 2 } else { 1 };
+    let width = (digits.len() as u32 * 4 - 1) * scale;
+    let left = (size - width) / 2;
+    let top = (size - 5 * scale) / 2;
+    for (index, &digit) in digits.iter().enumerate() {
+        let origin = left + index as u32 * 4 * scale;
+        for (row, bits) in DIGITS[digit].iter().enumerate() {
+            for column in 0..3u32 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for sy in 0..scale {
+                    for sx in 0..scale {
+                        set(
+                            origin + column * scale + sx,
+                            top + row as u32 * scale + sy,
+                            [0xff, 0xff, 0xff, 0xff],
+                        );
+                    }
+                }
+            }
+        }
+    }
+    tauri::image::Image::new_owned(rgba, size, size)
+}
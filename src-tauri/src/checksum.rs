@@ -0,0 +1,43 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::Emitter;
+
+// 与 notes.json 同目录保存其内容（按磁盘上的字节，加密时为密文）的 SHA-256
+pub const CHECKSUM_FILE: &str = "notes.json.sha256";
+// 校验不一致时发出；backup 为改用的备份文件名，没有可用备份时为 None
+pub const MISMATCH_EVENT: &str = "notes-checksum-mismatch";
+
+#[derive(Serialize, Clone)]
+pub struct ChecksumMismatch {
+    pub backup: Option<String>,
+}
+
+pub fn digest(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+// 每次写入 notes.json 后调用；写入失败时删除旧的校验文件，宁可不校验也不能让正确的文件被判为损坏
+pub fn update(app_dir: &Path, data: &[u8]) {
+    let path = app_dir.join(CHECKSUM_FILE);
+    if let Err(e) = crate::write_atomic(&path, digest(data).as_bytes()) {
+        tracing::warn!("Failed to save notes checksum: {}", e);
+        let _ = fs::remove_file(&path);
+    }
+}
+
+// 没有校验文件（旧版本写入的数据）时视为通过
+pub fn verify(app_dir: &Path, data: &[u8]) -> bool {
+    match fs::read_to_string(app_dir.join(CHECKSUM_FILE)) {
+        Ok(expected) => expected.trim().eq_ignore_ascii_case(&digest(data)),
+        Err(_) => true,
+    }
+}
+
+pub fn notify_mismatch(app_handle: &tauri::AppHandle, backup: Option<String>) {
+    if let Err(e) = app_handle.emit(MISMATCH_EVENT, ChecksumMismatch { backup }) {
+        tracing::warn!("Failed to emit checksum mismatch: {}", e);
+    }
+}
@@ -0,0 +1,29 @@
+use tauri::Manager;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::crypto::PassphraseState;
+use crate::error::AppError;
+
+// 由正文首行生成的标题最多保留的字符数
+const MAX_TITLE_CHARS: usize = 80;
+
+fn title_from(text: &str) -> String {
+    let first_line = text.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default();
+    first_line.chars().take(MAX_TITLE_CHARS).collect()
+}
+
+// 以剪贴板文本为正文新建笔记，标题取首个非空行；返回新笔记的 id
+pub fn create_note(app_handle: &tauri::AppHandle) -> Result<String, AppError> {
+    let text = app_handle
+        .clipboard()
+        .read_text()
+        .map_err(|_| AppError::InvalidInput("Clipboard has no text".to_string()))?;
+    if text.trim().is_empty() {
+        return Err(AppError::InvalidInput("Clipboard has no text".to_string()));
+    }
+
+    let passphrase = app_handle.state::<PassphraseState>();
+    let note = crate::insert_new_note(app_handle, &passphrase, title_from(&text), text)?;
+    crate::watcher::notify_changed(app_handle, "notes.json");
+    Ok(note.id)
+}
@@ -0,0 +1,111 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    // notes.json / tasks.json
+    #[default]
+    Json,
+    // 内嵌 SQLite 数据库 sticky.db
+    Sqlite,
+    // 每条笔记一个文件：notes/<id>.json + notes/index.json；任务仍保存在 tasks.json
+    Files,
+    // 每条笔记一个 Markdown 文件：notes_md/<id>.md，字段写在 YAML front matter 中；任务仍保存在 tasks.json
+    Markdown,
+}
+
+// Ctrl+Q 与窗口关闭按钮的行为；托盘菜单中的“退出”始终退出应用
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuitBehavior {
+    #[default]
+    Exit,
+    // 只隐藏窗口，进程继续在托盘中运行
+    MinimizeToTray,
+}
+
+// 应用配置，保存在 app_data_dir/config.json（不随存储目录迁移）
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct AppConfig {
+    // 自定义数据存储目录，未设置时使用 app_data_dir
+    pub storage_path: Option<String>,
+    // 是否加密保存笔记，默认明文以兼容已有数据
+    pub encryption_enabled: bool,
+    // 只读模式：开启后拒绝前端的保存请求，直到手动关闭
+    pub readonly: bool,
+    pub storage_backend: StorageBackend,
+    // notes.json / tasks.json 是否保存为带缩进的多行 JSON，便于查看与比较差异；默认紧凑格式
+    pub pretty_json: bool,
+    pub quit_behavior: QuitBehavior,
+    // 上次聚焦的笔记 id，下次启动时通过 restore-active-note 恢复
+    pub active_note: Option<String>,
+    // 窗口置顶偏好，未设置时沿用 tauri.conf.json 中的配置
+    pub always_on_top: Option<bool>,
+    // 是否在任务栏中隐藏窗口，未设置时沿用 tauri.conf.json 中的配置
+    pub skip_taskbar: Option<bool>,
+    // 主窗口贴靠的屏幕角落，启动及移到其它显示器时重新贴靠；未设置时不贴靠
+    pub window_anchor: Option<crate::window_anchor::Corner>,
+    // 窗口不透明度（0.2–1.0），未设置时完全不透明
+    pub window_opacity: Option<f64>,
+    // 任务到期前多少分钟提醒，未设置时为 reminders::DEFAULT_LEAD_MINUTES
+    pub reminder_lead_minutes: Option<u32>,
+    // 本地 HTTP API 端口，未设置时为 api::DEFAULT_PORT
+    pub api_port: Option<u16>,
+    // 导出 PDF 使用的字体文件（需支持中文），未设置时在常见系统字体中查找
+    pub pdf_font_path: Option<String>,
+    // 保留的滚动备份数量，未设置时为 backup::DEFAULT_RETENTION
+    pub backup_retention: Option<u32>,
+    // 定时备份的间隔小时数，0 表示关闭，未设置时为 scheduled_backup::DEFAULT_INTERVAL_HOURS
+    pub backup_interval_hours: Option<u32>,
+    // 是否记录本地使用统计（metrics.json），默认关闭
+    pub metrics_enabled: bool,
+    // 是否在每个命令完成后发出 command-timing 事件，默认关闭
+    pub timing_enabled: bool,
+    // 错误信息使用的语言标签（如 zh-CN），未设置时跟随系统语言
+    pub locale: Option<String>,
+    // 单条笔记正文与全部笔记的大小上限（字节），未设置时为 size_limits 中的默认值
+    pub max_note_bytes: Option<u64>,
+    pub max_total_bytes: Option<u64>,
+    // 已弹出为独立窗口的笔记 id，启动时恢复
+    pub note_windows: Vec<String>,
+}
+
+pub fn config_file(app_handle: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    Ok(crate::app_data_dir(app_handle)?.join("config.json"))
+}
+
+// 配置文件缺失或无法解析时返回默认配置
+pub fn load(app_handle: &tauri::AppHandle) -> AppConfig {
+    config_file(app_handle)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+// 与 load 不同，文件无法解析时返回错误，供手动编辑后重新加载时提示；文件缺失时返回默认配置
+pub fn load_strict(app_handle: &tauri::AppHandle) -> Result<AppConfig, AppError> {
+    let path = config_file(app_handle)?;
+    if !path.exists() {
+        return Ok(AppConfig::default());
+    }
+    let contents = fs::read_to_string(&path).map_err(AppError::io("Failed to read config"))?;
+    serde_json::from_str(&contents).map_err(AppError::serialization("Failed to parse config.json"))
+}
+
+pub fn save(app_handle: &tauri::AppHandle, config: &AppConfig) -> Result<(), AppError> {
+    let app_dir = crate::app_data_dir(app_handle)?;
+    fs::create_dir_all(&app_dir).map_err(AppError::io("Failed to create app directory"))?;
+
+    // 带缩进保存，便于通过 open_config_file 手动编辑
+    let contents = serde_json::to_string_pretty(config)
+        .map_err(AppError::serialization("Failed to serialize config"))?;
+    crate::write_atomic(&app_dir.join("config.json"), contents.as_bytes())
+        .map_err(AppError::io("Failed to save config"))
+}
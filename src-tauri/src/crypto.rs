@@ -0,0 +1,85 @@
+use std::sync::Mutex;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+
+use crate::error::AppError;
+
+// 密文文件格式：MAGIC | salt(16) | nonce(12) | AES-256-GCM 密文
+const MAGIC: &[u8] = b"STICKYENC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+const WRONG_PASSPHRASE: &str = "Incorrect passphrase";
+
+// 当前会话的口令，只保存在内存中，重启后需重新输入
+#[derive(Default)]
+pub struct PassphraseState(Mutex<Option<String>>);
+
+impl PassphraseState {
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, passphrase: String) {
+        *self.0.lock().unwrap() = Some(passphrase);
+    }
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key<Aes256Gcm>, AppError> {
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut_slice())
+        .map_err(|e| AppError::Encryption(format!("Failed to derive key: {}", e)))?;
+    Ok(key)
+}
+
+// 每次加密都使用新的 salt 与 nonce
+pub fn encrypt(passphrase: &str, plaintext: &[u8]) -> Result<Vec<u8>, AppError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new(&key)
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| AppError::Encryption("Failed to encrypt notes".to_string()))?;
+
+    let mut data = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(nonce.as_slice());
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+pub fn decrypt(passphrase: &str, data: &[u8]) -> Result<Vec<u8>, AppError> {
+    let header_len = MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if !is_encrypted(data) || data.len() < header_len {
+        return Err(AppError::Encryption("Encrypted notes file is malformed".to_string()));
+    }
+
+    let salt = &data[MAGIC.len()..MAGIC.len() + SALT_LEN];
+    let nonce = Nonce::from_slice(&data[MAGIC.len() + SALT_LEN..header_len]);
+    let key = derive_key(passphrase, salt)?;
+    // GCM 认证失败几乎总是口令错误所致
+    Aes256Gcm::new(&key)
+        .decrypt(nonce, &data[header_len..])
+        .map_err(|_| AppError::Encryption(WRONG_PASSPHRASE.to_string()))
+}
+
+// 将磁盘上的内容还原为 JSON 文本：密文需要口令，明文原样返回
+pub fn decode(data: Vec<u8>, passphrase: Option<&str>) -> Result<String, AppError> {
+    let plaintext = if is_encrypted(&data) {
+        let passphrase = passphrase.ok_or(AppError::Locked)?;
+        decrypt(passphrase, &data)?
+    } else {
+        data
+    };
+    String::from_utf8(plaintext).map_err(AppError::serialization("Notes are not valid UTF-8"))
+}
@@ -0,0 +1,42 @@
+use serde_json::Value;
+
+use crate::tasks::Task;
+
+const HEADER: [&str; 5] = ["id", "title", "completed", "due_at", "created_at"];
+
+// 含逗号、引号或换行的字段用双引号包裹，内部引号写成两个
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// 前端字段为 createdAt，兼容 created_at；缺失或为 null 时输出空单元格
+fn created_at(task: &Task) -> String {
+    match task.extra.get("createdAt").or_else(|| task.extra.get("created_at")) {
+        Some(Value::String(created_at)) => created_at.clone(),
+        Some(Value::Number(created_at)) => created_at.to_string(),
+        _ => String::new(),
+    }
+}
+
+// 带 UTF-8 BOM 并以 CRLF 换行，Excel 直接打开时中文不会乱码
+pub fn export(tasks: &[Task]) -> String {
+    let mut csv = String::from('\u{feff}');
+    csv.push_str(&HEADER.join(","));
+    csv.push_str("\r\n");
+    for task in tasks {
+        let row = [
+            escape(&task.id),
+            escape(&task.text),
+            task.completed.to_string(),
+            escape(task.due_at.as_deref().unwrap_or_default()),
+            escape(&created_at(task)),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
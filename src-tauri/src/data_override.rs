@@ -0,0 +1,16 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// 设置该环境变量后，所有数据（配置、笔记、日志等）直接保存在指定目录，
+// 优先于便携模式与自定义存储目录，便于测试时指向临时目录
+pub const ENV_VAR: &str = "STICKY_DATA_DIR";
+
+// 未设置或为空时返回 None；结果在首次调用（启动时）确定，运行期间不变
+pub fn data_dir() -> Option<PathBuf> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = std::env::var_os(ENV_VAR).filter(|dir| !dir.is_empty())?;
+        Some(PathBuf::from(dir))
+    })
+    .clone()
+}
@@ -0,0 +1,85 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{Emitter, Manager, Url};
+
+use crate::crypto::PassphraseState;
+use crate::error::AppError;
+
+pub const SCHEME: &str = "sticky";
+
+// 处理完链接后发给前端的事件
+pub const DEEP_LINK_EVENT: &str = "deep-link";
+
+#[derive(Serialize, Clone)]
+pub struct DeepLinkEvent {
+    // "created"：新建了笔记；"open"：打开已有笔记
+    pub action: &'static str,
+    pub id: String,
+}
+
+// 冷启动时前端尚未开始监听，处理结果暂存于此，由前端加载后通过 take_pending_deep_links 取走
+#[derive(Default)]
+pub struct PendingDeepLinks(Mutex<Vec<DeepLinkEvent>>);
+
+pub fn take_pending(app_handle: &tauri::AppHandle) -> Vec<DeepLinkEvent> {
+    std::mem::take(&mut *app_handle.state::<PendingDeepLinks>().0.lock().unwrap())
+}
+
+// 支持的链接：
+//   sticky://note/new?title=...&body=...  新建笔记
+//   sticky://note/<id>                    打开已有笔记
+fn perform(app_handle: &tauri::AppHandle, url: &Url) -> Result<DeepLinkEvent, AppError> {
+    if url.scheme() != SCHEME || url.host_str() != Some("note") {
+        return Err(AppError::InvalidInput(format!("Unsupported link: {}", url)));
+    }
+    let passphrase = app_handle.state::<PassphraseState>();
+    match url.path().trim_matches('/') {
+        "new" => {
+            let mut title = String::new();
+            let mut body = String::new();
+            for (key, value) in url.query_pairs() {
+                match key.as_ref() {
+                    "title" => title = value.into_owned(),
+                    "body" => body = value.into_owned(),
+                    _ => {}
+                }
+            }
+            let note = crate::insert_new_note(app_handle, &passphrase, title, body)?;
+            Ok(DeepLinkEvent {
+                action: "created",
+                id: note.id,
+            })
+        }
+        "" => Err(AppError::InvalidInput(format!("Missing note id: {}", url))),
+        id => {
+            let app_dir = crate::storage_dir(app_handle)?;
+            let notes =
+                crate::notes::parse(&crate::read_notes_json(app_handle, &app_dir, &passphrase)?)?;
+            crate::notes::find(&notes, id)?;
+            Ok(DeepLinkEvent {
+                action: "open",
+                id: id.to_string(),
+            })
+        }
+    }
+}
+
+// cold_start 为 true 表示应用由该链接启动，结果暂存而不是立即发出事件
+pub fn handle(app_handle: &tauri::AppHandle, urls: Vec<Url>, cold_start: bool) {
+    for url in urls {
+        let event = match perform(app_handle, &url) {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!("Failed to handle deep link {}: {}", url, e);
+                continue;
+            }
+        };
+        if cold_start {
+            app_handle.state::<PendingDeepLinks>().0.lock().unwrap().push(event);
+        } else if let Err(e) = app_handle.emit(DEEP_LINK_EVENT, event) {
+            tracing::warn!("Failed to emit {}: {}", DEEP_LINK_EVENT, e);
+        }
+    }
+    crate::show_main_window(app_handle);
+}
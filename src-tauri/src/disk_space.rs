@@ -0,0 +1,57 @@
+use std::io;
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::error::AppError;
+
+// 写盘前可用空间低于此值时发出 low-disk-space，写入仍会继续尝试
+pub const LOW_SPACE_BYTES: u64 = 10 * 1024 * 1024;
+
+pub const LOW_SPACE_EVENT: &str = "low-disk-space";
+
+#[derive(Serialize, Clone)]
+pub struct LowSpaceEvent {
+    pub path: String,
+    pub free_bytes: u64,
+}
+
+// 检查存储目录所在卷的可用空间；目录尚未创建时检查最近的已存在上级目录，查询失败时忽略
+pub fn warn_if_low(app_handle: &tauri::AppHandle, dir: &Path) {
+    let Some(existing) = dir.ancestors().find(|path| path.exists()) else {
+        return;
+    };
+    let free_bytes = match fs2::available_space(existing) {
+        Ok(free_bytes) => free_bytes,
+        Err(e) => {
+            tracing::warn!("Failed to query free disk space: {}", e);
+            return;
+        }
+    };
+    if free_bytes >= LOW_SPACE_BYTES {
+        return;
+    }
+
+    tracing::warn!("Low disk space: {} KB free in {}", free_bytes / 1024, dir.display());
+    let event = LowSpaceEvent {
+        path: dir.display().to_string(),
+        free_bytes,
+    };
+    if let Err(e) = app_handle.emit(LOW_SPACE_EVENT, event) {
+        tracing::warn!("Failed to emit low disk space event: {}", e);
+    }
+}
+
+// 磁盘已满导致的写入失败改写为明确的提示，其余错误原样返回
+pub fn explain_full(error: AppError) -> AppError {
+    match error {
+        AppError::Io { context, source } if source.kind() == io::ErrorKind::StorageFull => {
+            AppError::Io {
+                context: format!("{}: the disk is full, free up some space and try again", context),
+                source,
+            }
+        }
+        error => error,
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::notes::Note;
+use crate::tasks::Task;
+
+// 去掉首尾空白并忽略大小写后比较
+fn normalize(text: &str) -> String {
+    text.trim().to_lowercase()
+}
+
+// 返回标题与正文规范化后完全相同的笔记 id 分组，组与组内都按笔记原有顺序排列
+pub fn find(notes: &[Note]) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut index_by_key: HashMap<(String, String), usize> = HashMap::new();
+    for note in notes {
+        let key = (normalize(&note.title), normalize(&note.body));
+        match index_by_key.get(&key) {
+            Some(&index) => groups[index].push(note.id.clone()),
+            None => {
+                index_by_key.insert(key, groups.len());
+                groups.push(vec![note.id.clone()]);
+            }
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+// 合并时保留修改时间最新的一条；时间相同或无法解析时保留靠前的一条
+pub fn keeper<'a>(notes: &[&'a Note]) -> Option<&'a Note> {
+    notes
+        .iter()
+        .copied()
+        .rev()
+        .max_by_key(|note| note.updated_at.as_deref().and_then(crate::timestamp::parse))
+}
+
+// 任务按规范化后的标题与截止时间判断重复；同一时刻的不同写法视为相同的截止时间
+fn task_key(task: &Task) -> (String, Option<String>) {
+    let due_at = task.due_at.as_deref().map(|due_at| {
+        crate::timestamp::parse(due_at)
+            .map(|due| due.to_rfc3339())
+            .unwrap_or_else(|| due_at.trim().to_string())
+    });
+    (normalize(&task.text), due_at)
+}
+
+fn created_at(task: &Task) -> Option<DateTime<Utc>> {
+    task.extra
+        .get("createdAt")
+        .and_then(Value::as_str)
+        .and_then(crate::timestamp::parse)
+}
+
+// 删除重复任务并返回删除数量。每组保留一条：有已完成的保留已完成的，其次保留创建时间最新的
+// （相同时保留靠后的一条），保留的任务放在该组首次出现的位置；同时为缺少 id 的任务分配新 id
+pub fn dedupe_tasks(tasks: &mut Vec<Task>) -> usize {
+    let mut groups: Vec<Vec<Task>> = Vec::new();
+    let mut index_by_key: HashMap<(String, Option<String>), usize> = HashMap::new();
+    for task in tasks.drain(..) {
+        let key = task_key(&task);
+        match index_by_key.get(&key) {
+            Some(&index) => groups[index].push(task),
+            None => {
+                index_by_key.insert(key, groups.len());
+                groups.push(vec![task]);
+            }
+        }
+    }
+
+    let mut removed = 0;
+    for group in groups {
+        removed += group.len() - 1;
+        let keeper = group
+            .into_iter()
+            .max_by_key(|task| (task.completed, created_at(task)));
+        if let Some(mut task) = keeper {
+            if task.id.is_empty() {
+                task.id = uuid::Uuid::new_v4().to_string();
+            }
+            tasks.push(task);
+        }
+    }
+    removed
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+use std::io;
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+// 所有命令返回的错误类型；序列化为 { kind, message }，前端可按 kind 区分处理，message 用于直接展示。
+// 翻译表（见 i18n）中有的错误另带 code，message 按当前语言翻译
+#[derive(Debug)]
+pub enum AppError {
+    // 文件读写失败；context 说明正在进行的操作，序列化时额外带上 io_kind（如 PermissionDenied、StorageFull）
+    Io { context: String, source: io::Error },
+    Serialization(String),
+    NotFound(String),
+    InvalidInput(String),
+    // 数据已加密但尚未设置口令
+    Locked,
+    Encryption(String),
+    Database(String),
+    // 窗口、快捷键、自启动等系统接口调用失败
+    Platform(String),
+    // 配置的存储目录（如网络共享）暂时无法访问
+    StorageUnavailable(String),
+    // save_notes 的 expected_hash 与磁盘上的笔记不符；带上当前内容及其哈希供前端合并后重试
+    StaleWrite { current: String, hash: String },
+}
+
+impl AppError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Io { .. } => "io",
+            AppError::Serialization(_) => "serialization",
+            AppError::NotFound(_) => "not_found",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::Locked => "locked",
+            AppError::Encryption(_) => "encryption",
+            AppError::Database(_) => "database",
+            AppError::Platform(_) => "platform",
+            AppError::StorageUnavailable(_) => "storage_unavailable",
+            AppError::StaleWrite { .. } => "stale_write",
+        }
+    }
+
+    // 供 map_err 使用，如 .map_err(AppError::io("Failed to save notes"))
+    pub fn io(context: &'static str) -> impl FnOnce(io::Error) -> AppError {
+        move |source| AppError::Io {
+            context: context.to_string(),
+            source,
+        }
+    }
+
+    pub fn serialization<E: fmt::Display>(context: &'static str) -> impl FnOnce(E) -> AppError {
+        move |e| AppError::Serialization(format!("{}: {}", context, e))
+    }
+
+    pub fn platform<E: fmt::Display>(context: &'static str) -> impl FnOnce(E) -> AppError {
+        move |e| AppError::Platform(format!("{}: {}", context, e))
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Io { context, source } => write!(f, "{}: {}", context, source),
+            AppError::Locked => f.write_str("Notes are encrypted, set the passphrase to unlock them"),
+            AppError::StaleWrite { .. } => {
+                f.write_str("Notes were modified elsewhere since they were loaded")
+            }
+            AppError::Serialization(message)
+            | AppError::NotFound(message)
+            | AppError::InvalidInput(message)
+            | AppError::Encryption(message)
+            | AppError::Database(message)
+            | AppError::Platform(message)
+            | AppError::StorageUnavailable(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // 序列化即意味着错误将返回给调用方，借此记录命令失败
+        crate::logging::mark_failed();
+        tracing::warn!(kind = self.kind(), "{}", self);
+        let io_kind = match self {
+            AppError::Io { source, .. } => Some(format!("{:?}", source.kind())),
+            _ => None,
+        };
+        let stale = match self {
+            AppError::StaleWrite { current, hash } => Some((current, hash)),
+            _ => None,
+        };
+        // 日志保留英文原文，返回给前端的信息按当前语言翻译
+        let (code, message) = crate::i18n::localize(&self.to_string());
+        let fields = 2
+            + usize::from(code.is_some())
+            + usize::from(io_kind.is_some())
+            + 2 * usize::from(stale.is_some());
+        let mut state = serializer.serialize_struct("AppError", fields)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &message)?;
+        if let Some(code) = code {
+            state.serialize_field("code", code)?;
+        }
+        if let Some(io_kind) = io_kind {
+            state.serialize_field("io_kind", &io_kind)?;
+        }
+        if let Some((current, hash)) = stale {
+            state.serialize_field("current", current)?;
+            state.serialize_field("hash", hash)?;
+        }
+        state.end()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        AppError::Database(format!("Database error: {}", e))
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        AppError::Platform(e.to_string())
+    }
+}
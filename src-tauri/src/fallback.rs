@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::error::AppError;
+
+// 数据目录不可写、改用临时目录保存时发出，前端据此提示“正在保存到临时位置”
+pub const DEGRADED_EVENT: &str = "storage-degraded";
+
+#[derive(Serialize, Clone)]
+pub struct DegradedEvent {
+    pub path: String,
+    pub reason: String,
+}
+
+// 启动时检测到数据目录不可写后使用的临时目录；为 None 表示正常
+#[derive(Default)]
+pub struct StorageFallback(Mutex<Option<DegradedEvent>>);
+
+pub fn current(app_handle: &tauri::AppHandle) -> Option<PathBuf> {
+    let state = app_handle.state::<StorageFallback>();
+    let fallback = state.0.lock().unwrap();
+    fallback.as_ref().map(|event| PathBuf::from(&event.path))
+}
+
+// 启动及切换存储目录时通过写入探测文件检查数据目录（含 STICKY_DATA_DIR 指定的目录）；
+// 只读卷或无权限时改用系统临时目录，并带上原目录中已有的数据
+pub fn check(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    app_handle.state::<StorageFallback>().0.lock().unwrap().take();
+    let dir = crate::storage_dir(app_handle)?;
+    let probe = fs::create_dir_all(&dir).and_then(|_| crate::probe_writable(&dir));
+    let Err(e) = probe else {
+        return Ok(());
+    };
+
+    let fallback = std::env::temp_dir().join(&app_handle.config().identifier);
+    fs::create_dir_all(&fallback).map_err(AppError::io("Failed to create fallback directory"))?;
+    crate::probe_writable(&fallback).map_err(AppError::io("Fallback directory is not writable"))?;
+    // 复制与迁移存储目录时相同的数据（含各存储后端的目录与校验文件），保证仍能看到原来的笔记与任务；
+    // 临时目录中已有的文件（上次降级时写入）保留
+    let files = crate::relocate::collect(&dir).unwrap_or_else(|e| {
+        tracing::warn!("Failed to list data files to carry over: {}", e);
+        Vec::new()
+    });
+    for file in files {
+        let (source, target) = (dir.join(&file), fallback.join(&file));
+        if target.exists() {
+            continue;
+        }
+        let copied = target
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::copy(&source, &target));
+        if let Err(e) = copied {
+            tracing::warn!("Failed to copy {} to fallback directory: {}", file.display(), e);
+        }
+    }
+
+    tracing::warn!(
+        "Storage directory {} is not writable ({}), saving to {}",
+        dir.display(),
+        e,
+        fallback.display()
+    );
+    *app_handle.state::<StorageFallback>().0.lock().unwrap() = Some(DegradedEvent {
+        path: fallback.to_string_lossy().into_owned(),
+        reason: format!("Storage directory is not writable: {}", e),
+    });
+    Ok(())
+}
+
+// 降级原因，未降级时为 None
+pub fn reason(app_handle: &tauri::AppHandle) -> Option<String> {
+    let state = app_handle.state::<StorageFallback>();
+    let fallback = state.0.lock().unwrap();
+    fallback.as_ref().map(|event| event.reason.clone())
+}
+
+// 处于降级状态时向前端发出 storage-degraded；页面每次加载完成后调用
+pub fn notify(app_handle: &tauri::AppHandle) {
+    let event = app_handle.state::<StorageFallback>().0.lock().unwrap().clone();
+    if let Some(event) = event {
+        if let Err(e) = app_handle.emit(DEGRADED_EVENT, event) {
+            tracing::warn!("Failed to emit storage-degraded: {}", e);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::AppError;
+
+// 可用空间低于此值时磁盘检查不通过
+pub const MIN_FREE_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Serialize)]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct HealthReport {
+    // 所有检查均通过
+    pub ok: bool,
+    pub free_bytes: Option<u64>,
+    pub checks: Vec<HealthCheck>,
+}
+
+fn check(name: &'static str, result: Result<String, String>) -> HealthCheck {
+    match result {
+        Ok(message) => HealthCheck { name, passed: true, message },
+        Err(message) => HealthCheck { name, passed: false, message },
+    }
+}
+
+fn check_writable(app_dir: &Path) -> Result<String, String> {
+    if !app_dir.is_dir() {
+        return Err(format!("Data directory does not exist: {}", app_dir.display()));
+    }
+    crate::probe_writable(app_dir)
+        .map(|_| format!("Data directory is writable: {}", app_dir.display()))
+        .map_err(|e| format!("Data directory is not writable: {}", e))
+}
+
+fn check_notes(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &crate::crypto::PassphraseState,
+) -> Result<String, String> {
+    let notes = crate::read_notes_json(app_handle, app_dir, passphrase)
+        .and_then(|notes| crate::notes::parse(&notes));
+    match notes {
+        Ok(notes) => Ok(format!("Loaded {} notes", notes.len())),
+        // 加密且尚未解锁不算故障
+        Err(AppError::Locked) => Ok("Notes are encrypted and locked".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn check_tasks(app_handle: &tauri::AppHandle, app_dir: &Path) -> Result<String, String> {
+    crate::read_tasks_json(app_handle, app_dir)
+        .and_then(|tasks| crate::tasks::parse(&tasks))
+        .map(|tasks| format!("Loaded {} tasks", tasks.len()))
+        .map_err(|e| e.to_string())
+}
+
+// 依次检查数据目录可写、笔记与任务可正常解析、磁盘剩余空间；单项失败不影响其它检查
+pub fn run(
+    app_handle: &tauri::AppHandle,
+    passphrase: &crate::crypto::PassphraseState,
+) -> Result<HealthReport, AppError> {
+    let app_dir = crate::storage_dir(app_handle)?;
+    let free_bytes = fs2::available_space(&app_dir).ok();
+    let disk = match free_bytes {
+        Some(free) if free >= MIN_FREE_BYTES => Ok(format!("{} MB free", free / 1024 / 1024)),
+        Some(free) => Err(format!("Low disk space: {} MB free", free / 1024 / 1024)),
+        None => Err("Failed to query free disk space".to_string()),
+    };
+
+    let location = match crate::fallback::reason(app_handle) {
+        Some(reason) => Err(format!("{}, saving to temporary location {}", reason, app_dir.display())),
+        None => Ok(format!("Saving to {}", app_dir.display())),
+    };
+
+    let checks = vec![
+        check("storage_location", location),
+        check("storage_writable", check_writable(&app_dir)),
+        check("notes", check_notes(app_handle, &app_dir, passphrase)),
+        check("tasks", check_tasks(app_handle, &app_dir)),
+        check("disk_space", disk),
+    ];
+    Ok(HealthReport {
+        ok: checks.iter().all(|check| check.passed),
+        free_bytes,
+        checks,
+    })
+}
@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use tauri::Manager;
+
+use crate::error::AppError;
+
+// 最多保留的笔记快照数
+pub const MAX_ENTRIES: usize = 50;
+
+// 每次写入笔记后的快照（保存、修改单条笔记、导入、恢复备份等），仅保存在内存中，重启后清空。
+// cursor 指向当前内容对应的快照；撤销、重做只移动 cursor，不写盘
+#[derive(Default)]
+pub struct NotesHistory(Mutex<History>);
+
+#[derive(Default)]
+struct History {
+    entries: VecDeque<String>,
+    cursor: usize,
+}
+
+// 记录新保存的快照：丢弃 cursor 之后可重做的快照，超出上限时淘汰最早的快照
+pub fn record(app_handle: &tauri::AppHandle, notes: &str) {
+    let state = app_handle.state::<NotesHistory>();
+    let mut history = state.0.lock().unwrap();
+    // 保存的正是当前快照（如撤销预览后原样保存）时不重复记录，保留可重做的快照
+    if history.entries.get(history.cursor).is_some_and(|current| current == notes) {
+        return;
+    }
+    let keep = if history.entries.is_empty() { 0 } else { history.cursor + 1 };
+    history.entries.truncate(keep);
+    history.entries.push_back(notes.to_string());
+    if history.entries.len() > MAX_ENTRIES {
+        history.entries.pop_front();
+    }
+    history.cursor = history.entries.len() - 1;
+}
+
+pub fn undo(app_handle: &tauri::AppHandle) -> Result<String, AppError> {
+    let state = app_handle.state::<NotesHistory>();
+    let mut history = state.0.lock().unwrap();
+    if history.cursor == 0 {
+        return Err(AppError::NotFound("Nothing to undo".to_string()));
+    }
+    history.cursor -= 1;
+    Ok(history.entries[history.cursor].clone())
+}
+
+pub fn redo(app_handle: &tauri::AppHandle) -> Result<String, AppError> {
+    let state = app_handle.state::<NotesHistory>();
+    let mut history = state.0.lock().unwrap();
+    if history.cursor + 1 >= history.entries.len() {
+        return Err(AppError::NotFound("Nothing to redo".to_string()));
+    }
+    history.cursor += 1;
+    Ok(history.entries[history.cursor].clone())
+}
@@ -0,0 +1,133 @@
+use std::sync::{Mutex, OnceLock};
+
+// 返回给前端的错误信息的翻译表：code 为稳定的错误代码，en 为代码中使用的英文原文，
+// 错误信息等于原文或以“原文: ”开头（后接系统错误等细节）时替换为当前语言的译文
+struct Entry {
+    code: &'static str,
+    en: &'static str,
+    zh: &'static str,
+}
+
+const CATALOG: [Entry; 14] = [
+    Entry {
+        code: "load_notes_failed",
+        en: "Failed to load notes",
+        zh: "读取笔记失败",
+    },
+    Entry {
+        code: "save_notes_failed",
+        en: "Failed to save notes",
+        zh: "保存笔记失败",
+    },
+    Entry {
+        code: "parse_notes_failed",
+        en: "Failed to parse notes",
+        zh: "无法解析笔记",
+    },
+    Entry {
+        code: "serialize_notes_failed",
+        en: "Failed to serialize notes",
+        zh: "无法序列化笔记",
+    },
+    Entry {
+        code: "back_up_notes_failed",
+        en: "Failed to back up notes",
+        zh: "备份笔记失败",
+    },
+    Entry {
+        code: "load_tasks_failed",
+        en: "Failed to load tasks",
+        zh: "读取任务失败",
+    },
+    Entry {
+        code: "save_tasks_failed",
+        en: "Failed to save tasks",
+        zh: "保存任务失败",
+    },
+    Entry {
+        code: "parse_tasks_failed",
+        en: "Failed to parse tasks",
+        zh: "无法解析任务",
+    },
+    Entry {
+        code: "serialize_tasks_failed",
+        en: "Failed to serialize tasks",
+        zh: "无法序列化任务",
+    },
+    Entry {
+        code: "back_up_tasks_failed",
+        en: "Failed to back up tasks",
+        zh: "备份任务失败",
+    },
+    Entry {
+        code: "create_app_dir_failed",
+        en: "Failed to create app directory",
+        zh: "无法创建数据目录",
+    },
+    Entry {
+        code: "notes_locked",
+        en: "Notes are encrypted, set the passphrase to unlock them",
+        zh: "笔记已加密，请输入口令解锁",
+    },
+    Entry {
+        code: "stale_write",
+        en: "Notes were modified elsewhere since they were loaded",
+        zh: "笔记在加载后已被其它程序修改",
+    },
+    Entry {
+        code: "read_only",
+        en: "Read-only mode is enabled",
+        zh: "已开启只读模式",
+    },
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Language {
+    English,
+    Chinese,
+}
+
+// 用户通过 set_locale 指定的语言，优先于系统语言；启动时从配置读取
+static OVERRIDE: Mutex<Option<String>> = Mutex::new(None);
+static SYSTEM_LOCALE: OnceLock<Option<String>> = OnceLock::new();
+
+pub fn set_override(tag: Option<String>) {
+    *OVERRIDE.lock().unwrap() = tag;
+}
+
+// 语言标签只允许字母、数字、- 与 _，如 zh-CN、en_US
+pub fn is_valid_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag.len() <= 35
+        && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// 当前生效的语言标签：用户指定的优先，否则为系统语言
+pub fn current_locale() -> Option<String> {
+    let custom = OVERRIDE.lock().unwrap().clone();
+    custom.or_else(|| SYSTEM_LOCALE.get_or_init(sys_locale::get_locale).clone())
+}
+
+// 目前只有中文译文，其余语言一律使用英文
+fn language() -> Language {
+    match current_locale() {
+        Some(tag) if tag.to_ascii_lowercase().starts_with("zh") => Language::Chinese,
+        _ => Language::English,
+    }
+}
+
+// 返回错误代码（翻译表中没有时为 None）与当前语言下的错误信息
+pub fn localize(message: &str) -> (Option<&'static str>, String) {
+    let matched = CATALOG.iter().find_map(|entry| {
+        let rest = message.strip_prefix(entry.en)?;
+        (rest.is_empty() || rest.starts_with(": ")).then_some((entry, rest))
+    });
+    let Some((entry, rest)) = matched else {
+        return (None, message.to_string());
+    };
+    let text = match language() {
+        Language::Chinese => format!("{}{}", entry.zh, rest),
+        Language::English => message.to_string(),
+    };
+    (Some(entry.code), text)
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::notes::Note;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    // 不影响使用，仅供清理参考
+    Info,
+    // 数据可用，但部分字段会被忽略或显示异常
+    Warning,
+    // 可能导致笔记被覆盖或无法定位
+    Error,
+}
+
+#[derive(Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    // 与问题相关的笔记 id；缺少 id 的笔记为其在数组中的位置，如 "#3"
+    pub note: Option<String>,
+    // 与问题相关的文件（相对于数据目录）
+    pub file: Option<String>,
+    pub message: String,
+    // 是否已在本次扫描中自动修复
+    pub fixed: bool,
+}
+
+#[derive(Serialize)]
+pub struct IntegrityReport {
+    pub notes_scanned: usize,
+    pub fixed: usize,
+    pub issues: Vec<Issue>,
+}
+
+fn note_issue(severity: Severity, note: String, message: String) -> Issue {
+    Issue {
+        severity,
+        note: Some(note),
+        file: None,
+        message,
+        fixed: false,
+    }
+}
+
+fn file_issue(severity: Severity, file: String, message: &str) -> Issue {
+    Issue {
+        severity,
+        note: None,
+        file: Some(file),
+        message: message.to_string(),
+        fixed: false,
+    }
+}
+
+// 逐条检查笔记：id 非空且唯一，时间为合法的 ISO-8601，颜色为 #rrggbb，标签合法
+pub fn scan_notes(notes: &[Note]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for note in notes.iter().filter(|note| !note.id.is_empty()) {
+        *counts.entry(note.id.as_str()).or_default() += 1;
+    }
+    let mut duplicates: Vec<(&str, usize)> =
+        counts.into_iter().filter(|(_, count)| *count > 1).collect();
+    duplicates.sort();
+    for (id, count) in duplicates {
+        let message = format!("Id is shared by {} notes", count);
+        issues.push(note_issue(Severity::Error, id.to_string(), message));
+    }
+
+    for (index, note) in notes.iter().enumerate() {
+        if note.id.is_empty() {
+            let message = "Note has no id".to_string();
+            issues.push(note_issue(Severity::Error, format!("#{}", index), message));
+            continue;
+        }
+        let timestamps = [
+            ("created_at", &note.created_at),
+            ("updated_at", &note.updated_at),
+            ("remind_at", &note.remind_at),
+        ];
+        for (field, value) in timestamps {
+            if let Some(value) = value {
+                if crate::timestamp::parse(value).is_none() {
+                    let message = format!("{} is not ISO-8601: {}", field, value);
+                    issues.push(note_issue(Severity::Warning, note.id.clone(), message));
+                }
+            }
+        }
+        if let Some(color) = &note.color {
+            if !crate::notes::is_hex_color(color) {
+                let message = format!("Color is not #rrggbb: {}", color);
+                issues.push(note_issue(Severity::Warning, note.id.clone(), message));
+            }
+        }
+        if let Err(e) = crate::tags::validate(std::slice::from_ref(note)) {
+            issues.push(note_issue(Severity::Warning, note.id.clone(), e.to_string()));
+        }
+    }
+    issues
+}
+
+// 为缺少 id 的笔记分配新 id，返回修复数量；只有这一类问题可以无损地自动修复
+pub fn fix_missing_ids(notes: &mut [Note], issues: &mut [Issue]) -> usize {
+    let mut fixed = 0;
+    for (index, note) in notes.iter_mut().enumerate() {
+        if !note.id.is_empty() {
+            continue;
+        }
+        note.id = uuid::Uuid::new_v4().to_string();
+        let position = format!("#{}", index);
+        if let Some(issue) = issues
+            .iter_mut()
+            .find(|issue| issue.note.as_deref() == Some(position.as_str()))
+        {
+            issue.note = Some(note.id.clone());
+            issue.fixed = true;
+        }
+        fixed += 1;
+    }
+    fixed
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+// 数据目录中遗留的文件：中断的原子写入留下的 .tmp，修复、去重等操作前保存的一次性副本，
+// 以及备份目录中无法识别、不会被列出或轮换清理的文件
+pub fn scan_files(app_dir: &Path) -> Vec<Issue> {
+    const ONE_OFF_MARKERS: [&str; 2] = [".pre-", ".corrupt-"];
+
+    let mut issues = Vec::new();
+    for name in file_names(app_dir) {
+        if name.ends_with(".tmp") {
+            let message = "Leftover from an interrupted save";
+            issues.push(file_issue(Severity::Warning, name, message));
+        } else if ONE_OFF_MARKERS.iter().any(|marker| name.contains(marker)) {
+            let message = "One-off copy kept before a repair; delete it once no longer needed";
+            issues.push(file_issue(Severity::Info, name, message));
+        }
+    }
+
+    let backups_dir = crate::backup::backups_dir(app_dir);
+    for name in file_names(&backups_dir) {
+        let known = crate::backup::is_backup_name(&name)
+            || crate::backup::is_archive_name(&name)
+            || crate::scheduled_backup::is_backup_name(&name)
+            || name.starts_with("pre-import-");
+        if !known {
+            let file = format!("backups/{}", name);
+            let message = "Not a recognized backup, it is never listed or rotated";
+            issues.push(file_issue(Severity::Info, file, message));
+        }
+    }
+    issues
+}
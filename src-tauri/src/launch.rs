@@ -0,0 +1,32 @@
+use std::sync::OnceLock;
+
+// 开机自启时由 autostart 插件附带的启动参数
+pub const AUTOSTART_ARG: &str = "--autostart";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LaunchReason {
+    User,
+    Autostart,
+}
+
+impl LaunchReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LaunchReason::User => "user",
+            LaunchReason::Autostart => "autostart",
+        }
+    }
+}
+
+static REASON: OnceLock<LaunchReason> = OnceLock::new();
+
+// 由启动参数判断本次启动是否来自开机自启；结果在进程内保持不变
+pub fn reason() -> LaunchReason {
+    *REASON.get_or_init(|| {
+        if std::env::args().skip(1).any(|arg| arg == AUTOSTART_ARG) {
+            LaunchReason::Autostart
+        } else {
+            LaunchReason::User
+        }
+    })
+}
@@ -1,135 +1,2833 @@
+mod api;
+mod archive;
+mod attachments;
+mod autosave;
+mod backup;
+mod backup_diff;
+mod badge;
+mod checksum;
+mod clipboard;
+mod config;
+mod crypto;
+mod data_override;
+mod csv;
+mod deep_link;
+mod disk_space;
+mod duplicates;
+mod logging;
+mod error;
+mod fallback;
+mod health;
+mod integrity;
+mod launch;
+mod history;
+mod i18n;
+mod markdown;
+mod markdown_files;
+mod merge;
+mod metrics;
+mod note_files;
+mod note_windows;
+mod notes;
+mod offline;
+mod pdf;
+mod portable;
+mod progress;
+mod relocate;
+mod scheduled_backup;
+mod reminders;
+mod save_queue;
+mod search;
+mod secure_notes;
+mod shortcuts;
+mod shutdown;
+mod size_limits;
+mod sqlite;
+mod stats;
+mod storage_ready;
+mod tags;
+mod tasks;
+mod timestamp;
+mod todoist;
+mod trash;
+mod watcher;
+mod window_anchor;
+mod window_state;
+mod workspace;
+
 use std::fs;
-use tauri::Manager;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, Manager};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
-struct TasksPayload {
-    tasks: serde_json::Value,
+use error::AppError;
+
+#[derive(Serialize, Deserialize)]
+struct TasksPayload {
+    tasks: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct LoadNotesResult {
+    notes: String,
+    // 为 true 表示原文件已损坏，被重命名备份并返回了空数组
+    recovered: bool,
+    corrupt_file: Option<String>,
+}
+
+#[derive(Serialize)]
+struct StorageStats {
+    storage_dir: String,
+    notes_bytes: u64,
+    tasks_bytes: u64,
+    // 仅 SQLite 后端使用
+    database_bytes: u64,
+    // 笔记已加密且尚未解锁时为 null
+    note_count: Option<usize>,
+    task_count: usize,
+    backup_count: usize,
+}
+
+#[derive(Serialize)]
+struct AppInfo {
+    name: String,
+    version: String,
+    tauri_version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    // 由 build.rs 在构建时写入，非 git 仓库中构建时为 null
+    git_commit: Option<&'static str>,
+    // 是否以便携模式运行（可执行文件旁有 portable.txt）
+    portable: bool,
+}
+
+// 先写入同目录下的临时文件（如 notes.json.tmp），成功后再 rename 覆盖目标文件。
+// 同一文件系统上 rename 是原子的，写入中途崩溃时原文件保持不变。
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents))
+}
+
+// write 负责向临时文件写入内容；测试中借此模拟写入中途失败
+fn write_atomic_with(
+    path: &Path,
+    write: impl FnOnce(&mut fs::File) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let tmp_path = tmp_path_for(path);
+    let written = fs::File::create(&tmp_path).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    fs::rename(&tmp_path, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp_path);
+    })
+}
+
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// 应用数据目录；便携模式下为可执行文件旁的 data/
+fn app_data_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    if let Some(dir) = data_override::data_dir() {
+        return Ok(dir);
+    }
+    if let Some(dir) = portable::data_dir() {
+        return Ok(dir);
+    }
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(AppError::platform("Failed to get app data directory"))
+}
+
+// 解析实际的数据存储目录：启动时检测到目录不可写则使用临时目录（STICKY_DATA_DIR 同样检测）；
+// 否则设置了 STICKY_DATA_DIR 时直接使用，再其次为配置中的自定义目录，无效时回退到 app_data_dir
+fn storage_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    if let Some(path) = fallback::current(app_handle) {
+        return Ok(path);
+    }
+    if let Some(path) = data_override::data_dir() {
+        return Ok(path);
+    }
+    if let Some(path) = config::load(app_handle).storage_path {
+        let path = PathBuf::from(path);
+        if path.is_dir() {
+            return Ok(path);
+        }
+    }
+    app_data_dir(app_handle)
+}
+
+// 通过写入并删除探测文件确认目录可写
+fn probe_writable(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(".write-probe");
+    fs::write(&probe, b"")?;
+    fs::remove_file(probe)
+}
+
+// 启动自检：数据目录可写、笔记与任务文件可解析、磁盘剩余空间，逐项返回结果
+#[tauri::command]
+fn self_check(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+) -> Result<health::HealthReport, AppError> {
+    health::run(&app_handle, &passphrase)
+}
+
+// 启用加密时把笔记 JSON 加密为密文，否则原样返回
+fn encode_notes(
+    app_handle: &tauri::AppHandle,
+    passphrase: &crypto::PassphraseState,
+    notes: &str,
+) -> Result<Vec<u8>, AppError> {
+    if !config::load(app_handle).encryption_enabled {
+        return Ok(notes.as_bytes().to_vec());
+    }
+    match passphrase.get() {
+        Some(passphrase) => crypto::encrypt(&passphrase, notes.as_bytes()),
+        None => Err(AppError::Locked),
+    }
+}
+
+// 读取并解密 notes.json，文件不存在时返回空数组；secure 笔记的正文同时解密（未解锁时为占位文本）
+fn read_notes_json(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &crypto::PassphraseState,
+) -> Result<String, AppError> {
+    read_stored_notes_json(app_handle, app_dir, passphrase)
+        .and_then(|notes| secure_notes::reveal(notes, passphrase.get().as_deref()))
+        .inspect_err(|e| tracing::error!("Failed to load notes: {}", e))
+}
+
+// 与 read_notes_json 相同，但 secure 笔记保持存储中的密文，供备份、归档等原样保存数据的场景使用
+fn read_stored_notes_json(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &crypto::PassphraseState,
+) -> Result<String, AppError> {
+    read_notes_json_from_backend(app_handle, app_dir, passphrase)
+        .inspect_err(|e| tracing::error!("Failed to load notes: {}", e))
+}
+
+fn read_notes_json_from_backend(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &crypto::PassphraseState,
+) -> Result<String, AppError> {
+    match config::load(app_handle).storage_backend {
+        config::StorageBackend::Sqlite => return notes::to_json(&sqlite::load_notes(app_dir)?),
+        config::StorageBackend::Files => return notes::to_json(&note_files::load_notes(app_dir)?),
+        config::StorageBackend::Markdown => {
+            return notes::to_json(&markdown_files::load_notes(app_dir)?)
+        }
+        config::StorageBackend::Json => {}
+    }
+
+    let notes_file = app_dir.join("notes.json");
+    if !notes_file.exists() {
+        return Ok("[]".to_string());
+    }
+    let data = fs::read(&notes_file).map_err(AppError::io("Failed to load notes"))?;
+    let data = verify_notes_checksum(app_handle, app_dir, passphrase, data);
+    let contents = crypto::decode(data, passphrase.get().as_deref())?;
+    parse_notes_file(app_handle, app_dir, passphrase, &contents)
+}
+
+// 从 notes.json 的内容取出存储形式的笔记数组，load_notes_with_recovery 与内部读取共用；
+// from_envelope 无法识别的对象（如 {}）按 coerce_object 修正后写回
+fn parse_notes_file(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &crypto::PassphraseState,
+    contents: &str,
+) -> Result<String, AppError> {
+    let error = match notes::from_envelope(contents) {
+        Ok(notes) => return Ok(notes),
+        Err(e) => e,
+    };
+    let Some((notes, coercion)) = notes::coerce_object(contents) else {
+        return Err(error);
+    };
+
+    // 写回修正后的内容，原文件会先留存在备份中。secure 笔记的正文已是密文，
+    // 直接写入后端而不经过 write_notes_json，否则会被再次加密或因未解锁而失败
+    tracing::warn!("notes.json is not a notes array ({}), rewriting it", error);
+    write_notes_json_to_backend(app_handle, app_dir, passphrase, &notes)?;
+    let event = CoercedEvent {
+        file: "notes.json",
+        coercion,
+    };
+    if let Err(e) = app_handle.emit(COERCED_EVENT, event) {
+        tracing::warn!("Failed to emit {}: {}", COERCED_EVENT, e);
+    }
+    Ok(notes)
+}
+
+// notes.json 结构异常并已被修正时发出，载荷说明是取出了其中的数组还是重置为空数组
+const COERCED_EVENT: &str = "storage-coerced";
+
+#[derive(Serialize, Clone)]
+struct CoercedEvent {
+    file: &'static str,
+    coercion: notes::Coercion,
+}
+
+// 校验 notes.json 的 SHA-256；不一致（磁盘损坏或同步未完成）时改用最近一份有效备份并通知前端，
+// 没有可用备份时仍使用原内容
+fn verify_notes_checksum(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &crypto::PassphraseState,
+    data: Vec<u8>,
+) -> Vec<u8> {
+    if checksum::verify(app_dir, &data) {
+        return data;
+    }
+    match backup::latest_valid(app_dir, passphrase.get().as_deref()) {
+        Some((filename, backup)) => {
+            tracing::warn!("notes.json checksum mismatch, loading backup {}", filename);
+            checksum::notify_mismatch(app_handle, Some(filename));
+            backup
+        }
+        None => {
+            tracing::warn!("notes.json checksum mismatch and no valid backup found");
+            checksum::notify_mismatch(app_handle, None);
+            data
+        }
+    }
+}
+
+// 备份并原子写入 notes.json，所有修改笔记的路径都应经由此处
+fn write_notes_json(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &crypto::PassphraseState,
+    notes: &str,
+) -> Result<(), AppError> {
+    let mut notes = notes::parse(notes)?;
+    let previous = read_notes_json(app_handle, app_dir, passphrase)
+        .and_then(|previous| notes::parse(&previous));
+    write_notes_with(app_handle, app_dir, previous, &mut notes, |notes| {
+        let stored = || read_notes_json_from_backend(app_handle, app_dir, passphrase);
+        let notes = notes::to_json(notes)?;
+        let notes = secure_notes::seal(&notes, passphrase.get().as_deref(), stored)?;
+        write_notes_json_to_backend(app_handle, app_dir, passphrase, &notes)
+    })
+}
+
+// 写入笔记的公共步骤，write_notes_json 与 save_note 的逐文件写入共用：磁盘空间提示、
+// 按存储中的上一版本计算 revision_count、大小检查，写入失败时说明磁盘已满，
+// 成功后计入保存次数并记录撤销历史。无法读取上一版本（如文件损坏）时不影响保存
+fn write_notes_with(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    previous: Result<Vec<notes::Note>, AppError>,
+    notes: &mut [notes::Note],
+    write: impl FnOnce(&[notes::Note]) -> Result<(), AppError>,
+) -> Result<(), AppError> {
+    disk_space::warn_if_low(app_handle, app_dir);
+    // 历史记录修改次数计算之前的内容：原样保存撤销预览时与当前快照相同，保留可重做的快照
+    let snapshot = notes::to_json(notes)?;
+    // 没有上一版本可比较时，修改次数沿用传入的值，大小检查按全部笔记都有变化处理
+    let previous = previous.unwrap_or_else(|e| {
+        tracing::warn!("Failed to count note revisions: {}", e);
+        Vec::new()
+    });
+    notes::count_revisions(&previous, notes);
+    size_limits::check(app_handle, &previous, notes)
+        .and_then(|_| write(notes))
+        .map_err(disk_space::explain_full)
+        .inspect_err(|e| tracing::error!("Failed to save notes: {}", e))?;
+    metrics::increment(app_handle, metrics::Counter::Saves);
+    history::record(app_handle, &snapshot);
+    Ok(())
+}
+
+// 直接写入存储形式（恢复备份、导入归档等）后记录撤销历史；历史中是前端看到的内容，secure 笔记先解密
+fn record_stored_history(
+    app_handle: &tauri::AppHandle,
+    passphrase: &crypto::PassphraseState,
+    stored: &str,
+) {
+    match secure_notes::reveal(stored.to_string(), passphrase.get().as_deref()) {
+        Ok(notes) => history::record(app_handle, &notes),
+        Err(e) => tracing::warn!("Failed to record notes history: {}", e),
+    }
+}
+
+fn write_notes_json_to_backend(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &crypto::PassphraseState,
+    notes: &str,
+) -> Result<(), AppError> {
+    // 所有写入都先确认自定义目录可访问，避免共享断开时写到回退的 app_data_dir
+    offline::probe(app_handle)?;
+    let config = config::load(app_handle);
+    match config.storage_backend {
+        config::StorageBackend::Sqlite => return sqlite::save_notes(app_dir, &notes::parse(notes)?),
+        config::StorageBackend::Files => return note_files::save_notes(app_dir, notes::parse(notes)?),
+        config::StorageBackend::Markdown => {
+            return markdown_files::save_notes(app_dir, notes::parse(notes)?)
+        }
+        config::StorageBackend::Json => {}
+    }
+
+    // 确保目录存在
+    fs::create_dir_all(app_dir).map_err(AppError::io("Failed to create app directory"))?;
+    
+    // 覆盖前留存一份历史版本；备份失败不应阻止保存
+    if let Err(e) = backup::snapshot_notes(app_dir, backup::retention(app_handle)) {
+        tracing::warn!("Failed to back up notes: {}", e);
+    }
+
+    let envelope = notes::to_envelope(notes, config.pretty_json)?;
+    let contents = encode_notes(app_handle, passphrase, &envelope)?;
+    let notes_file = app_dir.join("notes.json");
+    watcher::record(app_handle, &notes_file, &contents);
+    write_atomic(&notes_file, &contents).map_err(AppError::io("Failed to save notes"))?;
+    checksum::update(app_dir, &contents);
+    Ok(())
+}
+
+// 供没有命令参数可用的内部调用（如防抖写盘）使用
+fn persist_notes(app_handle: &tauri::AppHandle, notes: &str) -> Result<(), AppError> {
+    // 与 save_notes_now 相同的校验，防抖写盘不能绕过
+    tags::validate(&notes::parse(notes)?)?;
+    let _guard = save_queue::lock(app_handle);
+    if let Err(e) = offline::probe(app_handle) {
+        let save = offline::PendingSave {
+            notes: notes.to_string(),
+            base: None,
+            expected_hash: None,
+        };
+        offline::buffer(app_handle, save);
+        return Err(e);
+    }
+    offline::discard(app_handle);
+    let app_dir = storage_dir(app_handle)?;
+    let passphrase = app_handle.state::<crypto::PassphraseState>();
+    write_notes_json(app_handle, &app_dir, &passphrase, notes)
+}
+
+// 读取-修改-写回笔记的通用流程，供只修改个别笔记的命令使用
+fn modify_notes<T>(
+    app_handle: &tauri::AppHandle,
+    passphrase: &crypto::PassphraseState,
+    modify: impl FnOnce(&mut Vec<notes::Note>) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    ensure_editable(app_handle)?;
+    // 先写入尚未落盘的防抖内容（flush 自行加锁），避免其随后覆盖这次修改
+    autosave::flush(app_handle)?;
+    let _guard = save_queue::lock(app_handle);
+    let app_dir = storage_dir(app_handle)?;
+    let mut notes = notes::parse(&read_notes_json(app_handle, &app_dir, passphrase)?)?;
+    let result = modify(&mut notes)?;
+    write_notes_json(app_handle, &app_dir, passphrase, &notes::to_json(&notes)?)?;
+    Ok(result)
+}
+
+// 只读模式切换后发出，载荷为是否开启
+const READONLY_EVENT: &str = "readonly-changed";
+
+// 只读模式下拒绝前端的保存请求；modify_notes 与 modify_tasks 统一检查，其余直接写入的命令
+// （清空回收站、切换存储后端、首次设置口令等）各自调用
+fn ensure_editable(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    if config::load(app_handle).readonly {
+        return Err(AppError::InvalidInput("Read-only mode is enabled".to_string()));
+    }
+    Ok(())
+}
+
+// 开启或关闭只读模式（如演示、共享屏幕时防止误改）；持久化并发出 readonly-changed 供前端禁用输入
+#[tauri::command]
+fn set_readonly(app_handle: tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    let mut config = config::load(&app_handle);
+    config.readonly = enabled;
+    config::save(&app_handle, &config)?;
+    app_handle.emit(READONLY_EVENT, enabled)?;
+    Ok(())
+}
+
+// 启动时读取保存的只读状态
+#[tauri::command]
+fn get_readonly(app_handle: tauri::AppHandle) -> bool {
+    config::load(&app_handle).readonly
+}
+
+// notes.json / tasks.json 是否保存为带缩进的多行 JSON，在下次保存时生效；读取时两种格式均可
+#[tauri::command]
+fn set_pretty_json(app_handle: tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    let mut config = config::load(&app_handle);
+    config.pretty_json = enabled;
+    config::save(&app_handle, &config)
+}
+
+// 设置单条笔记正文与全部笔记的大小上限（字节），之后的保存超出时返回 InvalidInput，规则见 size_limits::check
+#[tauri::command]
+fn set_size_limits(
+    app_handle: tauri::AppHandle,
+    per_note_bytes: u64,
+    total_bytes: u64,
+) -> Result<(), AppError> {
+    if per_note_bytes == 0 || per_note_bytes > total_bytes {
+        return Err(AppError::InvalidInput(
+            "Per-note limit must be positive and no larger than the total limit".to_string(),
+        ));
+    }
+    let mut config = config::load(&app_handle);
+    config.max_note_bytes = Some(per_note_bytes);
+    config.max_total_bytes = Some(total_bytes);
+    config::save(&app_handle, &config)
+}
+
+// 保存按到达顺序串行执行；revision 为前端单调递增的修订号，比已写入的更旧时丢弃该次保存。
+// expected_hash 为前端加载时 load_notes 返回内容的 SHA-256（十六进制），
+// 磁盘上的笔记已被外部修改时返回 StaleWrite 而不覆盖
+#[tauri::command]
+async fn save_notes(
+    app_handle: tauri::AppHandle,
+    notes: String,
+    base: Option<String>,
+    revision: Option<u64>,
+    expected_hash: Option<String>,
+) -> Result<merge::MergeSummary, AppError> {
+    ensure_editable(&app_handle)?;
+    let queue = app_handle.state::<save_queue::SaveQueue>();
+    let mut last_written = queue.0.lock().await;
+    if save_queue::is_stale(*last_written, revision) {
+        tracing::warn!("Dropping stale save (revision {:?})", revision);
+        return Ok(merge::MergeSummary {
+            stale: true,
+            ..Default::default()
+        });
+    }
+
+    let handle = app_handle.clone();
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        save_notes_now(&handle, notes, base, expected_hash)
+    })
+    .await??;
+    if revision.is_some() {
+        *last_written = revision;
+    }
+    Ok(summary)
+}
+
+fn save_notes_now(
+    app_handle: &tauri::AppHandle,
+    notes: String,
+    base: Option<String>,
+    expected_hash: Option<String>,
+) -> Result<merge::MergeSummary, AppError> {
+    // 直接保存的内容更新，避免之后被旧的防抖内容覆盖
+    autosave::discard(app_handle);
+
+    let local = notes::parse(&notes)?;
+    tags::validate(&local)?;
+    // 存储目录（如网络共享）无法访问时缓存这次保存，恢复后由后台重试写入
+    if let Err(e) = offline::probe(app_handle) {
+        let save = offline::PendingSave {
+            notes,
+            base,
+            expected_hash,
+        };
+        offline::buffer(app_handle, save);
+        return Err(e);
+    }
+    offline::discard(app_handle);
+
+    let passphrase = app_handle.state::<crypto::PassphraseState>();
+    let app_dir = storage_dir(app_handle)?;
+    if let Some(expected_hash) = &expected_hash {
+        let current = read_notes_json(app_handle, &app_dir, &passphrase)?;
+        let hash = checksum::digest(current.as_bytes());
+        if !hash.eq_ignore_ascii_case(expected_hash.trim()) {
+            return Err(AppError::StaleWrite { current, hash });
+        }
+    }
+    // 传入前端上次加载的内容时，先与磁盘上可能已被其它设备同步修改的笔记合并
+    let Some(base) = base else {
+        write_notes_json(app_handle, &app_dir, &passphrase, &notes)?;
+        return Ok(merge::MergeSummary::default());
+    };
+    let base = notes::parse(&base)?;
+    let remote = notes::parse(&read_notes_json(app_handle, &app_dir, &passphrase)?)?;
+    let (merged, summary) = merge::three_way(&base, local, remote);
+    let merged = notes::to_json(&merged)?;
+    write_notes_json(app_handle, &app_dir, &passphrase, &merged)?;
+    Ok(summary)
+}
+
+// 返回上一个保存的笔记快照供前端预览，不写盘；确认后由前端调用 save_notes 保存
+#[tauri::command]
+fn undo_notes(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    history::undo(&app_handle)
+}
+
+// 返回下一个笔记快照（撤销之后），同样不写盘
+#[tauri::command]
+fn redo_notes(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    history::redo(&app_handle)
+}
+
+// 合并高频保存：只保留最新内容，静默 autosave::DEBOUNCE_INTERVAL 后写盘
+#[tauri::command]
+fn save_notes_debounced(app_handle: tauri::AppHandle, notes: String) {
+    // 没有返回值可以报错，只读模式下直接丢弃
+    if ensure_editable(&app_handle).is_err() {
+        return;
+    }
+    autosave::schedule(&app_handle, notes);
+}
+
+#[tauri::command]
+fn load_notes(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+) -> Result<String, AppError> {
+    load_notes_with_recovery(app_handle, passphrase).map(|result| result.notes)
+}
+
+#[tauri::command]
+fn load_notes_with_recovery(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+) -> Result<LoadNotesResult, AppError> {
+    offline::probe(&app_handle)?;
+    let app_dir = storage_dir(&app_handle)?;
+    if config::load(&app_handle).storage_backend != config::StorageBackend::Json {
+        return Ok(LoadNotesResult {
+            notes: read_notes_json(&app_handle, &app_dir, &passphrase)?,
+            recovered: false,
+            corrupt_file: None,
+        });
+    }
+    
+    let notes_file = app_dir.join("notes.json");
+    
+    if !notes_file.exists() {
+        return Ok(LoadNotesResult {
+            notes: "[]".to_string(), // 返回空数组
+            recovered: false,
+            corrupt_file: None,
+        });
+    }
+
+    let data = fs::read(&notes_file).map_err(AppError::io("Failed to load notes"))?;
+    let data = verify_notes_checksum(&app_handle, &app_dir, &passphrase, data);
+    recover_notes_file(&notes_file, data, passphrase.get().as_deref(), |contents| {
+        parse_notes_file(&app_handle, &app_dir, &passphrase, contents)
+    })
+}
+
+// 解密并解析 notes.json 的内容，无法解析时移走文件；parse 为 parse_notes_file
+fn recover_notes_file(
+    notes_file: &Path,
+    data: Vec<u8>,
+    passphrase: Option<&str>,
+    parse: impl FnOnce(&str) -> Result<String, AppError>,
+) -> Result<LoadNotesResult, AppError> {
+    // 口令缺失或错误时直接报错，不能把密文当作损坏文件处理
+    let contents = crypto::decode(data, passphrase)?;
+    match parse(&contents) {
+        Ok(notes) => Ok(LoadNotesResult {
+            notes: secure_notes::reveal(notes, passphrase)?,
+            recovered: false,
+            corrupt_file: None,
+        }),
+        // 只有内容无法解析才按损坏处理；版本过新等错误直接返回，不能移走文件
+        Err(AppError::Serialization(_)) => quarantine_notes_file(notes_file),
+        Err(e) => Err(e),
+    }
+}
+
+// 内容无法解析：保留损坏文件（notes.json.corrupt-<毫秒>）以便手动恢复，并以空列表启动
+fn quarantine_notes_file(notes_file: &Path) -> Result<LoadNotesResult, AppError> {
+    let corrupt_file = notes_file.with_file_name(format!("notes.json.corrupt-{}", now_millis()));
+    fs::rename(notes_file, &corrupt_file)
+        .map_err(AppError::io("Failed to move corrupt notes file"))?;
+
+    Ok(LoadNotesResult {
+        notes: "[]".to_string(),
+        recovered: true,
+        corrupt_file: Some(corrupt_file.to_string_lossy().into_owned()),
+    })
+}
+
+// 把单条笔记导出为可直接分享的独立 HTML 文件
+#[tauri::command]
+fn export_note_html(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+    dest_path: String,
+) -> Result<(), AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    let note = notes::find(&notes, &id)?;
+
+    fs::write(&dest_path, markdown::standalone_html(note))
+        .map_err(AppError::io("Failed to export note"))
+}
+
+#[tauri::command]
+fn export_markdown(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    dest_path: String,
+) -> Result<(), AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+
+    fs::write(&dest_path, markdown::export(&notes))
+        .map_err(AppError::io("Failed to export markdown"))
+}
+
+#[tauri::command]
+fn print_note_pdf(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+    dest_path: String,
+) -> Result<(), AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    let note = notes::find(&notes, &id)?;
+
+    let font = pdf::find_font(config::load(&app_handle).pdf_font_path.as_deref())?;
+    pdf::render(note, &font, Path::new(&dest_path))
+}
+
+// 追加导入的笔记，不覆盖已有笔记；返回导入数量。
+// 在后台线程执行，期间发出 import-progress，结束后发出 import-complete
+#[tauri::command]
+async fn import_markdown(app_handle: tauri::AppHandle, src_path: String) -> Result<usize, AppError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<usize, AppError> {
+        let source = fs::read_to_string(&src_path)
+            .map_err(AppError::io("Failed to read markdown"))?;
+        let imported = markdown::import(&source);
+        let count = imported.len();
+        if count > 0 {
+            let passphrase = app_handle.state::<crypto::PassphraseState>();
+            modify_notes(&app_handle, &passphrase, |notes| {
+                progress::extend_in_batches(&app_handle, notes, imported);
+                Ok(())
+            })?;
+        }
+        progress::complete(&app_handle, count);
+        Ok(count)
+    })
+    .await?
+}
+
+// 笔记正文的字数、字符数（含与不含空白）与行数
+#[tauri::command]
+fn note_stats(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+) -> Result<stats::NoteStats, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    let note = notes::find(&notes, &id)?;
+    Ok(stats::count(&note.body))
+}
+
+// 笔记正文（Markdown）渲染后的安全 HTML，供前端直接显示预览
+#[tauri::command]
+fn render_note_html(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+) -> Result<String, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    let note = notes::find(&notes, &id)?;
+    Ok(markdown::render_html(&note.body))
+}
+
+// 修改时间在 [start, end] 内的笔记，按修改时间从新到旧排列；仅有日期的边界包含当天全天，
+// 没有修改时间的笔记不计入
+#[tauri::command]
+fn notes_modified_between(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    start: String,
+    end: String,
+) -> Result<Vec<notes::Note>, AppError> {
+    let invalid =
+        |value: &str| AppError::InvalidInput(format!("Invalid date, expected ISO-8601: {}", value));
+    let from = timestamp::parse_start(&start).ok_or_else(|| invalid(&start))?;
+    let to = timestamp::parse(&end).ok_or_else(|| invalid(&end))?;
+    if from > to {
+        return Err(AppError::InvalidInput("Start must not be after end".to_string()));
+    }
+
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    let mut matching: Vec<_> = notes
+        .into_iter()
+        .filter_map(|note| {
+            let updated_at = note.updated_at.as_deref().and_then(timestamp::parse)?;
+            (from <= updated_at && updated_at <= to).then_some((updated_at, note))
+        })
+        .collect();
+    matching.sort_by(|(a, _), (b, _)| b.cmp(a));
+    Ok(matching.into_iter().map(|(_, note)| note).collect())
+}
+
+// 带有指定标签的笔记，忽略大小写
+#[tauri::command]
+fn filter_notes_by_tag(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    tag: String,
+) -> Result<Vec<notes::Note>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    Ok(tags::filter(notes, &tag))
+}
+
+// 所有标签及其使用次数，按次数从多到少排列
+#[tauri::command]
+fn list_all_tags(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+) -> Result<Vec<(String, usize)>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    Ok(tags::count(&notes))
+}
+
+#[tauri::command]
+fn search_notes(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    query: String,
+) -> Result<Vec<search::SearchHit>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    Ok(search::search(&notes, &query))
+}
+
+#[tauri::command]
+fn set_note_color(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+    color: String,
+) -> Result<(), AppError> {
+    if !notes::is_hex_color(&color) {
+        return Err(AppError::InvalidInput(format!("Invalid color, expected #rrggbb: {}", color)));
+    }
+
+    modify_notes(&app_handle, &passphrase, |notes| {
+        let note = notes::find_mut(notes, &id)?;
+        note.color = Some(color.to_lowercase());
+        notes::touch(note);
+        Ok(())
+    })
+}
+
+// 设置笔记的提醒时间，到时由后台扫描发出系统通知；与置顶一样不更新 updated_at
+#[tauri::command]
+fn set_note_reminder(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+    remind_at: String,
+) -> Result<(), AppError> {
+    let remind_at = timestamp::parse(&remind_at).ok_or_else(|| {
+        AppError::InvalidInput(format!("Invalid reminder time, expected ISO-8601: {}", remind_at))
+    })?;
+
+    modify_notes(&app_handle, &passphrase, |notes| {
+        let note = notes::find_mut(notes, &id)?;
+        note.remind_at = Some(remind_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true));
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn clear_note_reminder(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+) -> Result<(), AppError> {
+    modify_notes(&app_handle, &passphrase, |notes| {
+        notes::find_mut(notes, &id)?.remind_at = None;
+        Ok(())
+    })
+}
+
+// 拖动排序：只移动一条笔记并保存，前端无需重新发送整个数组
+#[tauri::command]
+fn reorder_note(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+    new_index: usize,
+) -> Result<(), AppError> {
+    modify_notes(&app_handle, &passphrase, |notes| notes::reorder(notes, &id, new_index))
+}
+
+// 标记或取消 secure：只有这些笔记的正文在存储中单独加密，标题与其它字段仍可搜索。
+// 两种情况都需要先输入口令（set_passphrase 或 unlock_secure_notes）
+#[tauri::command]
+fn set_note_secure(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+    secure: bool,
+) -> Result<(), AppError> {
+    if passphrase.get().is_none() {
+        return Err(AppError::Locked);
+    }
+    modify_notes(&app_handle, &passphrase, |notes| {
+        notes::find_mut(notes, &id)?.secure = secure;
+        Ok(())
+    })
+}
+
+// 输入用于 secure 笔记的口令，只保存在内存中；与 set_passphrase 不同，不会加密整个 notes.json
+#[tauri::command]
+fn unlock_secure_notes(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    pass: String,
+) -> Result<(), AppError> {
+    if pass.is_empty() {
+        return Err(AppError::InvalidInput("Passphrase must not be empty".to_string()));
+    }
+    let app_dir = storage_dir(&app_handle)?;
+    secure_notes::verify(&read_stored_notes_json(&app_handle, &app_dir, &passphrase)?, &pass)?;
+    passphrase.set(pass);
+    Ok(())
+}
+
+// 置顶不算内容修改，不更新 updated_at
+#[tauri::command]
+fn set_note_pinned(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+    pinned: bool,
+) -> Result<(), AppError> {
+    modify_notes(&app_handle, &passphrase, |notes| {
+        notes::find_mut(notes, &id)?.pinned = pinned;
+        Ok(())
+    })
+}
+
+// 与 load_notes 相同的数据，但置顶笔记在前、其余按修改时间倒序
+#[tauri::command]
+fn load_notes_sorted(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+) -> Result<Vec<notes::Note>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let mut notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    notes::sort_pinned_first(&mut notes);
+    Ok(notes)
+}
+
+// 最近修改的笔记（最多 notes::MAX_RECENT 条），供“最近”列表使用
+#[tauri::command]
+fn get_recent_notes(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    limit: usize,
+) -> Result<Vec<notes::Note>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    Ok(notes::recent(notes, limit))
+}
+
+// 正文修改次数最多的笔记（最多 notes::MAX_RECENT 条），用于区分常更新与写完即止的笔记
+#[tauri::command]
+fn most_edited_notes(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    limit: usize,
+) -> Result<Vec<notes::Note>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    Ok(notes::most_edited(notes, limit))
+}
+
+// 删除标题与正文都为空白的笔记（见 notes::is_blank），返回删除数量；
+// 改写前在数据目录留存一份原数据（notes.json.pre-trim-<毫秒>，启用加密时同样加密）
+#[tauri::command]
+fn trim_empty_notes(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+) -> Result<usize, AppError> {
+    ensure_editable(&app_handle)?;
+    let _guard = save_queue::lock(&app_handle);
+    let app_dir = storage_dir(&app_handle)?;
+    let mut notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    let before = notes.len();
+    notes.retain(|note| !notes::is_blank(note));
+    let removed = before - notes.len();
+    if removed == 0 {
+        return Ok(0);
+    }
+
+    let stored = read_stored_notes_json(&app_handle, &app_dir, &passphrase)?;
+    let original = encode_notes(&app_handle, &passphrase, &notes::to_envelope(&stored, false)?)?;
+    let backup_file = app_dir.join(format!("notes.json.pre-trim-{}", now_millis()));
+    write_atomic(&backup_file, &original).map_err(AppError::io("Failed to back up notes"))?;
+    write_notes_json(&app_handle, &app_dir, &passphrase, &notes::to_json(&notes)?)?;
+    Ok(removed)
+}
+
+// 检查笔记数据（id、时间、颜色、标签）与数据目录中的遗留文件，按严重程度列出问题；
+// fix 为 true 时为缺少 id 的笔记分配新 id 并保存，其余问题只报告
+#[tauri::command]
+fn run_integrity_scan(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    fix: Option<bool>,
+) -> Result<integrity::IntegrityReport, AppError> {
+    let fix = fix.unwrap_or(false);
+    if fix {
+        ensure_editable(&app_handle)?;
+    }
+    // 只有需要写回修复结果时才与保存串行
+    let _guard = fix.then(|| save_queue::lock(&app_handle));
+    let app_dir = storage_dir(&app_handle)?;
+    let mut notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    let mut issues = integrity::scan_notes(&notes);
+    let mut fixed = 0;
+    if fix {
+        fixed = integrity::fix_missing_ids(&mut notes, &mut issues);
+        if fixed > 0 {
+            write_notes_json(&app_handle, &app_dir, &passphrase, &notes::to_json(&notes)?)?;
+        }
+    }
+    issues.extend(integrity::scan_files(&app_dir));
+
+    Ok(integrity::IntegrityReport {
+        notes_scanned: notes.len(),
+        fixed,
+        issues,
+    })
+}
+
+// 为笔记附加一张图片（png、jpg、gif，最大 5 MB），返回保存位置相对于数据目录的路径
+#[tauri::command]
+fn attach_image(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+    image_bytes: Vec<u8>,
+    ext: String,
+) -> Result<String, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let mut saved = None;
+    let result = modify_notes(&app_handle, &passphrase, |notes| {
+        let note = notes::find_mut(notes, &id)?;
+        let path = attachments::save(&app_dir, &id, &image_bytes, &ext)?;
+        saved = Some(path.clone());
+        note.attachments.push(path.clone());
+        notes::touch(note);
+        Ok(path)
+    });
+    // 笔记写入失败时删除刚保存的图片，避免留下无人引用的文件
+    if let (Err(_), Some(path)) = (&result, &saved) {
+        if let Err(e) = attachments::remove(&app_dir, &id, path) {
+            tracing::warn!("Failed to remove unsaved attachment {}: {}", path, e);
+        }
+    }
+    result
+}
+
+#[tauri::command]
+fn remove_attachment(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+    path: String,
+) -> Result<(), AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    modify_notes(&app_handle, &passphrase, |notes| {
+        let note = notes::find_mut(notes, &id)?;
+        let index = note
+            .attachments
+            .iter()
+            .position(|attachment| *attachment == path)
+            .ok_or_else(|| AppError::NotFound(format!("Attachment not found: {}", path)))?;
+        note.attachments.remove(index);
+        notes::touch(note);
+        Ok(())
+    })?;
+    // 笔记写入成功后才删除文件；删除失败只留下无人引用的文件
+    if let Err(e) = attachments::remove(&app_dir, &id, &path) {
+        tracing::warn!("Failed to remove attachment {}: {}", path, e);
+    }
+    Ok(())
+}
+
+// 软删除：把笔记移入回收站（trash.json）
+#[tauri::command]
+fn delete_note(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+) -> Result<(), AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    modify_notes(&app_handle, &passphrase, |notes| {
+        let index = notes
+            .iter()
+            .position(|note| note.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Note not found: {}", id)))?;
+        // 先写回收站再写笔记：中途失败时宁可重复也不丢失。
+        // 附件保留到从回收站彻底删除时再清理，恢复的笔记仍带有原来的图片
+        let mut trash = trash::load(&app_dir, &passphrase)?;
+        trash.push(trash::TrashEntry::new(notes.remove(index)));
+        trash::save(&app_handle, &app_dir, &passphrase, trash)
+    })
+}
+
+#[tauri::command]
+fn find_duplicate_notes(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+) -> Result<Vec<Vec<String>>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let notes = notes::parse(&read_notes_json(&app_handle, &app_dir, &passphrase)?)?;
+    Ok(duplicates::find(&notes))
+}
+
+// 保留其中修改时间最新的笔记，其余移入回收站；返回保留的笔记 id
+#[tauri::command]
+fn merge_notes(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    ids: Vec<String>,
+) -> Result<String, AppError> {
+    if ids.len() < 2 {
+        return Err(AppError::InvalidInput(
+            "At least two notes are required to merge".to_string(),
+        ));
+    }
+
+    let app_dir = storage_dir(&app_handle)?;
+    modify_notes(&app_handle, &passphrase, |notes| {
+        let mut selected = Vec::with_capacity(ids.len());
+        for id in &ids {
+            let note = notes
+                .iter()
+                .find(|note| note.id == *id)
+                .ok_or_else(|| AppError::NotFound(format!("Note not found: {}", id)))?;
+            selected.push(note);
+        }
+        let keep = duplicates::keeper(&selected)
+            .map(|note| note.id.clone())
+            .unwrap_or_default();
+
+        let (merged, kept): (Vec<_>, Vec<_>) = notes
+            .drain(..)
+            .partition(|note| note.id != keep && ids.contains(&note.id));
+        *notes = kept;
+        // 先写回收站再写笔记：中途失败时宁可重复也不丢失
+        let mut trash = trash::load(&app_dir, &passphrase)?;
+        trash.extend(merged.into_iter().map(trash::TrashEntry::new));
+        trash::save(&app_handle, &app_dir, &passphrase, trash)?;
+        Ok(keep)
+    })
+}
+
+#[tauri::command]
+fn list_trash(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+) -> Result<Vec<trash::TrashEntry>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    trash::load(&app_dir, &passphrase)
+}
+
+#[tauri::command]
+fn restore_note(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    id: String,
+) -> Result<(), AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let mut trash = trash::load(&app_dir, &passphrase)?;
+    let index = trash
+        .iter()
+        .position(|entry| entry.note.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Note not found in trash: {}", id)))?;
+    let entry = trash.remove(index);
+
+    // 先写回笔记再更新回收站
+    modify_notes(&app_handle, &passphrase, |notes| {
+        notes.push(entry.note);
+        Ok(())
+    })?;
+    trash::save(&app_handle, &app_dir, &passphrase, trash)
+}
+
+#[tauri::command]
+fn empty_trash(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+) -> Result<(), AppError> {
+    ensure_editable(&app_handle)?;
+    let app_dir = storage_dir(&app_handle)?;
+    // 回收站无法读取（如尚未解锁）时仍然清空，只是不清理附件
+    let removed = trash::load(&app_dir, &passphrase).unwrap_or_else(|e| {
+        tracing::warn!("Failed to read trash, attachments are kept: {}", e);
+        Vec::new()
+    });
+    trash::save(&app_handle, &app_dir, &passphrase, Vec::new())?;
+    remove_trashed_attachments(&app_handle, &app_dir, &passphrase, &removed);
+    Ok(())
+}
+
+// 从回收站彻底删除笔记后清理其附件目录；中途失败时同一笔记可能仍在笔记中，
+// 这类附件保留。清理失败只留下无人引用的文件
+fn remove_trashed_attachments(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &crypto::PassphraseState,
+    removed: &[trash::TrashEntry],
+) {
+    if removed.iter().all(|entry| entry.note.attachments.is_empty()) {
+        return;
+    }
+    let notes = read_stored_notes_json(app_handle, app_dir, passphrase)
+        .and_then(|notes| notes::parse(&notes));
+    let notes = match notes {
+        Ok(notes) => notes,
+        Err(e) => {
+            tracing::warn!("Failed to read notes, attachments are kept: {}", e);
+            return;
+        }
+    };
+    for entry in removed.iter().filter(|entry| !entry.note.attachments.is_empty()) {
+        if notes.iter().any(|note| note.id == entry.note.id) {
+            continue;
+        }
+        if let Err(e) = attachments::remove_all(app_dir, &entry.note.id) {
+            tracing::warn!("Failed to remove attachments of note {}: {}", entry.note.id, e);
+        }
+    }
+}
+
+// 启动时清理过期的回收站条目；回收站已加密且尚未解锁时跳过
+fn purge_expired_trash(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let app_dir = storage_dir(app_handle)?;
+    let passphrase = app_handle.state::<crypto::PassphraseState>();
+    let mut trash = trash::load(&app_dir, &passphrase)?;
+    let expired = trash::purge_expired(&mut trash);
+    if !expired.is_empty() {
+        trash::save(app_handle, &app_dir, &passphrase, trash)?;
+        remove_trashed_attachments(app_handle, &app_dir, &passphrase, &expired);
+    }
+    Ok(())
+}
+
+// 把笔记、任务、配置与备份打包为一个 zip 文件，用于整体备份或迁移到新机器
+#[tauri::command]
+fn export_archive(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    dest_path: String,
+) -> Result<(), AppError> {
+    workspace::export(&app_handle, &passphrase, Path::new(&dest_path))
+}
+
+// 从 export_archive 生成的 zip 恢复；覆盖前当前数据会先导出到备份目录
+#[tauri::command]
+fn import_archive(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    src_path: String,
+) -> Result<(), AppError> {
+    ensure_editable(&app_handle)?;
+    // 先写入尚未落盘的防抖内容，使其包含在导入前的备份中，且不会随后覆盖导入的笔记
+    autosave::flush(&app_handle)?;
+    let _guard = save_queue::lock(&app_handle);
+    workspace::import(&app_handle, &passphrase, Path::new(&src_path))?;
+    watcher::notify_changed(&app_handle, "notes.json");
+    Ok(())
+}
+
+#[tauri::command]
+fn list_backups(app_handle: tauri::AppHandle) -> Result<Vec<backup::BackupEntry>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+
+    backup::list(&app_dir).map_err(AppError::io("Failed to list backups"))
+}
+
+// 设置滚动备份的保留数量（至少为 1），下次保存笔记时生效
+#[tauri::command]
+fn set_backup_retention(app_handle: tauri::AppHandle, count: u32) -> Result<(), AppError> {
+    let mut config = config::load(&app_handle);
+    config.backup_retention = Some(count.max(backup::MIN_RETENTION as u32));
+    config::save(&app_handle, &config)
+}
+
+// 设置定时备份的间隔小时数，0 表示关闭定时备份
+#[tauri::command]
+fn set_backup_interval_hours(app_handle: tauri::AppHandle, n: u32) -> Result<(), AppError> {
+    let mut config = config::load(&app_handle);
+    config.backup_interval_hours = Some(n);
+    config::save(&app_handle, &config)
+}
+
+// 立即删除超出保留数量的旧备份，返回删除的数量
+#[tauri::command]
+fn prune_backups(app_handle: tauri::AppHandle) -> Result<usize, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    backup::prune(&app_dir, backup::retention(&app_handle))
+        .map_err(AppError::io("Failed to prune backups"))
+}
+
+// 把超过 7 天的备份按月合并为 zip 归档，减少备份目录中的文件数；返回移入归档的备份数。
+// list_backups、restore_backup 与 diff_backups 同样可以读取归档中的备份
+#[tauri::command]
+fn compact_backups(app_handle: tauri::AppHandle) -> Result<usize, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    backup::compact(&app_dir)
+}
+
+// 把笔记恢复为指定备份中的内容，完成后通知前端重新加载
+#[tauri::command]
+fn restore_backup(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    filename: String,
+) -> Result<(), AppError> {
+    ensure_editable(&app_handle)?;
+    // 恢复的备份取代当前内容，尚未落盘的防抖内容不再写入，否则会覆盖恢复的笔记
+    autosave::discard(&app_handle);
+    let _guard = save_queue::lock(&app_handle);
+    let app_dir = storage_dir(&app_handle)?;
+
+    // 按当前存储后端写回；secure 笔记的正文在备份中已是密文，直接写入后端而不再加密。
+    // json 后端写入前会先备份当前文件，使恢复操作本身也可撤销
+    let notes = backup::load_notes(&app_dir, &filename, passphrase.get().as_deref())?;
+    let notes = notes::to_json(&notes)?;
+    write_notes_json_to_backend(&app_handle, &app_dir, &passphrase, &notes)?;
+    record_stored_history(&app_handle, &passphrase, &notes);
+    watcher::notify_changed(&app_handle, "notes.json");
+    Ok(())
+}
+
+// 比较两份备份中的笔记，按 id 列出新增、删除与修改的笔记，便于选择恢复点；
+// 文件名为 "notes.json" 时使用当前数据
+#[tauri::command]
+fn diff_backups(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    a: String,
+    b: String,
+) -> Result<backup_diff::BackupDiff, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let load = |name: &str| -> Result<Vec<notes::Note>, AppError> {
+        if name == "notes.json" {
+            // 与备份一样保持 secure 笔记的密文，两边可直接比较
+            notes::parse(&read_stored_notes_json(&app_handle, &app_dir, &passphrase)?)
+        } else {
+            backup::load_notes(&app_dir, name, passphrase.get().as_deref())
+        }
+    };
+    Ok(backup_diff::diff(&load(&a)?, &load(&b)?))
+}
+
+// 设置口令：已加密时校验口令并解锁；首次设置时把现有明文笔记及备份迁移为密文
+#[tauri::command]
+fn set_passphrase(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    pass: String,
+) -> Result<(), AppError> {
+    if pass.is_empty() {
+        return Err(AppError::InvalidInput("Passphrase must not be empty".to_string()));
+    }
+    let config = config::load(&app_handle);
+    if config.storage_backend != config::StorageBackend::Json {
+        return Err(AppError::InvalidInput(
+            "Encryption is only supported with the JSON storage backend".to_string(),
+        ));
+    }
+    // 首次设置会重写笔记与备份，只读模式下拒绝；已加密时只是解锁，不受只读模式限制。
+    // 迁移前先写入尚未落盘的防抖内容，并在整个迁移期间暂停保存，避免明文在加密之后被写回
+    if !config.encryption_enabled {
+        ensure_editable(&app_handle)?;
+        autosave::flush(&app_handle)?;
+    }
+    let _guard = save_queue::lock(&app_handle);
+    let app_dir = storage_dir(&app_handle)?;
+    let notes_file = app_dir.join("notes.json");
+    if notes_file.exists() {
+        let data = fs::read(&notes_file).map_err(AppError::io("Failed to load notes"))?;
+        if crypto::is_encrypted(&data) {
+            crypto::decrypt(&pass, &data)?;
+        } else {
+            let encrypted = crypto::encrypt(&pass, &data)?;
+            watcher::record(&app_handle, &notes_file, &encrypted);
+            write_atomic(&notes_file, &encrypted)
+                .map_err(AppError::io("Failed to encrypt notes"))?;
+            checksum::update(&app_dir, &encrypted);
+        }
+    }
+    backup::encrypt_all(&app_dir, &pass)?;
+
+    // 先设置口令再开启加密，开启后的读写都能取得口令
+    passphrase.set(pass);
+    let mut config = config::load(&app_handle);
+    if !config.encryption_enabled {
+        config.encryption_enabled = true;
+        config::save(&app_handle, &config)?;
+    }
+    Ok(())
+}
+
+fn read_tasks_json(app_handle: &tauri::AppHandle, app_dir: &Path) -> Result<String, AppError> {
+    read_tasks_json_from_backend(app_handle, app_dir)
+        .inspect_err(|e| tracing::error!("Failed to load tasks: {}", e))
+}
+
+fn read_tasks_json_from_backend(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+) -> Result<String, AppError> {
+    if config::load(app_handle).storage_backend == config::StorageBackend::Sqlite {
+        let tasks = sqlite::load_tasks(app_dir)?;
+        return serde_json::to_string(&tasks).map_err(AppError::serialization("Failed to serialize tasks"));
+    }
+
+    let tasks_file = app_dir.join("tasks.json");
+    
+    if tasks_file.exists() {
+        fs::read_to_string(tasks_file).map_err(AppError::io("Failed to load tasks"))
+    } else {
+        Ok("[]".to_string()) // 返回空数组
+    }
+}
+
+fn write_tasks_json(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    tasks: &serde_json::Value,
+) -> Result<(), AppError> {
+    disk_space::warn_if_low(app_handle, app_dir);
+    write_tasks_json_to_backend(app_handle, app_dir, tasks)
+        .map_err(disk_space::explain_full)
+        .inspect_err(|e| tracing::error!("Failed to save tasks: {}", e))?;
+    metrics::increment(app_handle, metrics::Counter::Saves);
+    // 任务变化后同步更新未完成数量角标；更新失败不影响保存
+    if let Err(e) = badge::apply(app_handle, badge::incomplete_count(tasks)) {
+        tracing::warn!("Failed to update task badge: {}", e);
+    }
+    Ok(())
+}
+
+fn write_tasks_json_to_backend(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    tasks: &serde_json::Value,
+) -> Result<(), AppError> {
+    // 与笔记相同，写入前先确认自定义目录可访问
+    offline::probe(app_handle)?;
+    let config = config::load(app_handle);
+    if config.storage_backend == config::StorageBackend::Sqlite {
+        let tasks = tasks
+            .as_array()
+            .ok_or_else(|| AppError::InvalidInput("Tasks must be an array".to_string()))?;
+        return sqlite::save_tasks(app_dir, tasks);
+    }
+
+    // 确保目录存在
+    fs::create_dir_all(app_dir).map_err(AppError::io("Failed to create app directory"))?;
+    
+    let tasks_file = app_dir.join("tasks.json");
+    let tasks_json = if config.pretty_json {
+        serde_json::to_string_pretty(tasks)
+    } else {
+        serde_json::to_string(tasks)
+    };
+    let tasks_json = tasks_json.map_err(AppError::serialization("Failed to serialize tasks"))?;
+    watcher::record(app_handle, &tasks_file, tasks_json.as_bytes());
+    write_atomic(&tasks_file, tasks_json.as_bytes()).map_err(AppError::io("Failed to save tasks"))
+}
+
+#[tauri::command]
+fn save_tasks(app_handle: tauri::AppHandle, payload: TasksPayload) -> Result<(), AppError> {
+    ensure_editable(&app_handle)?;
+    let tasks = tasks::validate(payload.tasks)?;
+    let tasks = serde_json::to_value(&tasks)
+        .map_err(AppError::serialization("Failed to serialize tasks"))?;
+
+    let _guard = save_queue::lock(&app_handle);
+    offline::probe(&app_handle)?;
+    let app_dir = storage_dir(&app_handle)?;
+    write_tasks_json(&app_handle, &app_dir, &tasks)
+}
+
+// 同时保存笔记与任务：先写笔记再写任务，任务写入失败时把笔记恢复为写入前的内容，
+// 保证两者要么都更新、要么都保持原样
+#[tauri::command]
+fn save_all(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    notes: String,
+    payload: TasksPayload,
+) -> Result<(), AppError> {
+    ensure_editable(&app_handle)?;
+    // 写盘前完成校验，避免因输入无效而回滚
+    let tasks = tasks::validate(payload.tasks)?;
+    let tasks = serde_json::to_value(&tasks)
+        .map_err(AppError::serialization("Failed to serialize tasks"))?;
+    tags::validate(&notes::parse(&notes)?)?;
+
+    autosave::discard(&app_handle);
+    let _guard = save_queue::lock(&app_handle);
+    let app_dir = storage_dir(&app_handle)?;
+    // 回滚时恢复写入前的状态：json 后端原样写回 notes.json 的原始内容（原本不存在时删除），
+    // 其它后端写回存储中的内容；都不再重新加密、计入修改次数或留存备份
+    let previous_file = fs::read(app_dir.join("notes.json")).ok();
+    let previous_notes = read_stored_notes_json(&app_handle, &app_dir, &passphrase)?;
+    write_notes_json(&app_handle, &app_dir, &passphrase, &notes)?;
+    if let Err(e) = write_tasks_json(&app_handle, &app_dir, &tasks) {
+        let rollback = match config::load(&app_handle).storage_backend {
+            config::StorageBackend::Json => {
+                restore_notes_file(&app_handle, &app_dir, previous_file.as_deref())
+            }
+            _ => write_notes_json_to_backend(&app_handle, &app_dir, &passphrase, &previous_notes),
+        };
+        match rollback {
+            Ok(()) => record_stored_history(&app_handle, &passphrase, &previous_notes),
+            Err(rollback) => tracing::error!("Failed to roll back notes: {}", rollback),
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+// 把 notes.json 恢复为之前读取的原始内容，previous 为 None 表示原本没有该文件
+fn restore_notes_file(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    previous: Option<&[u8]>,
+) -> Result<(), AppError> {
+    let notes_file = app_dir.join("notes.json");
+    let Some(data) = previous else {
+        let _ = fs::remove_file(app_dir.join(checksum::CHECKSUM_FILE));
+        return fs::remove_file(&notes_file).map_err(AppError::io("Failed to remove notes"));
+    };
+    watcher::record(app_handle, &notes_file, data);
+    write_atomic(&notes_file, data).map_err(AppError::io("Failed to restore notes"))?;
+    checksum::update(app_dir, data);
+    Ok(())
+}
+
+#[tauri::command]
+fn load_tasks(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    offline::probe(&app_handle)?;
+    let app_dir = storage_dir(&app_handle)?;
+    read_tasks_json(&app_handle, &app_dir)
+}
+
+// 立即按当前任务更新托盘提示与未完成数量角标，返回未完成的任务数
+#[tauri::command]
+fn refresh_task_badge(app_handle: tauri::AppHandle) -> Result<usize, AppError> {
+    badge::refresh(&app_handle)
+}
+
+// 任务搜索框：按标题搜索任务，可只看未完成的，结果按截止时间与优先级排列
+#[tauri::command]
+fn search_tasks(
+    app_handle: tauri::AppHandle,
+    query: String,
+    only_incomplete: bool,
+) -> Result<Vec<tasks::Task>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let tasks = tasks::parse(&read_tasks_json(&app_handle, &app_dir)?)?;
+    Ok(search::search_tasks(tasks, &query, only_incomplete))
+}
+
+// 按优先级（high -> low）及截止时间排序后的任务
+#[tauri::command]
+fn load_tasks_by_priority(app_handle: tauri::AppHandle) -> Result<Vec<tasks::Task>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let mut tasks = tasks::parse(&read_tasks_json(&app_handle, &app_dir)?)?;
+    tasks::sort_by_priority(&mut tasks);
+    Ok(tasks)
+}
+
+#[tauri::command]
+fn export_tasks_csv(app_handle: tauri::AppHandle, dest_path: String) -> Result<(), AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let tasks = tasks::parse(&read_tasks_json(&app_handle, &app_dir)?)?;
+
+    fs::write(&dest_path, csv::export(&tasks))
+        .map_err(AppError::io("Failed to export tasks"))
+}
+
+// 读取-修改-写回任务的通用流程
+fn modify_tasks<T>(
+    app_handle: &tauri::AppHandle,
+    modify: impl FnOnce(&mut Vec<tasks::Task>) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    ensure_editable(app_handle)?;
+    let _guard = save_queue::lock(app_handle);
+    let app_dir = storage_dir(app_handle)?;
+    let mut tasks = tasks::parse(&read_tasks_json(app_handle, &app_dir)?)?;
+    let result = modify(&mut tasks)?;
+    let tasks = serde_json::to_value(&tasks)
+        .map_err(AppError::serialization("Failed to serialize tasks"))?;
+    write_tasks_json(app_handle, &app_dir, &tasks)?;
+    Ok(result)
+}
+
+// 修复部分损坏的任务文件：丢弃无法解析的条目、补全缺失的 id 后写回；
+// 有改动时先把原内容保存为 tasks.json.pre-repair-<毫秒>
+#[tauri::command]
+fn repair_tasks(app_handle: tauri::AppHandle) -> Result<tasks::RepairReport, AppError> {
+    ensure_editable(&app_handle)?;
+    let _guard = save_queue::lock(&app_handle);
+    let app_dir = storage_dir(&app_handle)?;
+    let original = read_tasks_json(&app_handle, &app_dir)?;
+    let value: serde_json::Value = serde_json::from_str(&original)
+        .map_err(AppError::serialization("Failed to parse tasks"))?;
+    let (repaired, report) = tasks::repair(value)?;
+    if report.dropped == 0 && report.fixed == 0 {
+        return Ok(report);
+    }
+
+    let backup_file = app_dir.join(format!("tasks.json.pre-repair-{}", now_millis()));
+    write_atomic(&backup_file, original.as_bytes())
+        .map_err(AppError::io("Failed to back up tasks"))?;
+    let repaired = serde_json::to_value(&repaired)
+        .map_err(AppError::serialization("Failed to serialize tasks"))?;
+    write_tasks_json(&app_handle, &app_dir, &repaired)?;
+    Ok(report)
+}
+
+// 同步后清理重复任务：删除标题（忽略大小写与首尾空白）与截止时间都相同的任务，
+// 并为缺少 id 的任务补充 id；改写前在数据目录留存一份原文件。返回删除的数量
+#[tauri::command]
+fn dedupe_tasks(app_handle: tauri::AppHandle) -> Result<usize, AppError> {
+    ensure_editable(&app_handle)?;
+    let _guard = save_queue::lock(&app_handle);
+    let app_dir = storage_dir(&app_handle)?;
+    let original = read_tasks_json(&app_handle, &app_dir)?;
+    let mut deduped = tasks::parse(&original)?;
+    let missing_ids = deduped.iter().any(|task| task.id.is_empty());
+    let removed = duplicates::dedupe_tasks(&mut deduped);
+    if removed == 0 && !missing_ids {
+        return Ok(0);
+    }
+
+    let backup_file = app_dir.join(format!("tasks.json.pre-dedupe-{}", now_millis()));
+    write_atomic(&backup_file, original.as_bytes())
+        .map_err(AppError::io("Failed to back up tasks"))?;
+    let deduped = serde_json::to_value(&deduped)
+        .map_err(AppError::serialization("Failed to serialize tasks"))?;
+    write_tasks_json(&app_handle, &app_dir, &deduped)?;
+    Ok(removed)
+}
+
+// 按筛选条件批量完成、取消完成或删除任务，返回受影响的任务数；
+// 有改动时先把原内容保存为 tasks.json.pre-bulk-<毫秒>
+#[tauri::command]
+fn bulk_update_tasks(
+    app_handle: tauri::AppHandle,
+    filter: tasks::TaskFilter,
+    action: tasks::TaskAction,
+) -> Result<usize, AppError> {
+    ensure_editable(&app_handle)?;
+    let _guard = save_queue::lock(&app_handle);
+    let app_dir = storage_dir(&app_handle)?;
+    let original = read_tasks_json(&app_handle, &app_dir)?;
+    let mut updated = tasks::parse(&original)?;
+    let affected = tasks::bulk_update(&mut updated, &filter, action)?;
+    if affected == 0 {
+        return Ok(0);
+    }
+
+    let backup_file = app_dir.join(format!("tasks.json.pre-bulk-{}", now_millis()));
+    write_atomic(&backup_file, original.as_bytes())
+        .map_err(AppError::io("Failed to back up tasks"))?;
+    let updated = serde_json::to_value(&updated)
+        .map_err(AppError::serialization("Failed to serialize tasks"))?;
+    write_tasks_json(&app_handle, &app_dir, &updated)?;
+    if matches!(action, tasks::TaskAction::Complete) {
+        metrics::add(&app_handle, metrics::Counter::TasksCompleted, affected as u64);
+    }
+    Ok(affected)
+}
+
+// 从 Todoist 的 JSON 导出中导入任务，追加到现有任务之后；返回导入数量。
+// 与 import_markdown 一样在后台线程执行并发出导入进度
+#[tauri::command]
+async fn import_todoist(app_handle: tauri::AppHandle, src_path: String) -> Result<usize, AppError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<usize, AppError> {
+        let source = fs::read_to_string(&src_path)
+            .map_err(AppError::io("Failed to read Todoist export"))?;
+        let imported = todoist::import(&source)?;
+        let count = imported.len();
+        if count > 0 {
+            modify_tasks(&app_handle, |tasks| {
+                progress::extend_in_batches(&app_handle, tasks, imported);
+                Ok(())
+            })?;
+        }
+        progress::complete(&app_handle, count);
+        Ok(count)
+    })
+    .await?
+}
+
+// 完成任务；重复任务会自动生成下一期，返回新任务的 id
+#[tauri::command]
+fn complete_task(app_handle: tauri::AppHandle, id: String) -> Result<Option<String>, AppError> {
+    let mut newly_completed = false;
+    let next = modify_tasks(&app_handle, |tasks| {
+        newly_completed = tasks.iter().any(|task| task.id == id && !task.completed);
+        tasks::complete(tasks, &id)
+    })?;
+    if newly_completed {
+        metrics::increment(&app_handle, metrics::Counter::TasksCompleted);
+    }
+    Ok(next)
+}
+
+// 开启或关闭本地使用统计；关闭后保留已有数据，可用 reset_metrics 清空
+#[tauri::command]
+fn set_metrics_enabled(app_handle: tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    let mut config = config::load(&app_handle);
+    config.metrics_enabled = enabled;
+    config::save(&app_handle, &config)
+}
+
+// 本地使用统计：启动次数、新建笔记数、完成任务数与保存次数
+#[tauri::command]
+fn get_metrics(app_handle: tauri::AppHandle) -> metrics::Metrics {
+    metrics::load(&app_handle)
+}
+
+#[tauri::command]
+fn reset_metrics(app_handle: tauri::AppHandle) -> Result<(), AppError> {
+    metrics::reset(&app_handle)
+}
+
+// 指定错误信息使用的语言（如 zh-CN、en），传入空字符串恢复跟随系统语言；返回当前生效的语言标签
+#[tauri::command]
+fn set_locale(app_handle: tauri::AppHandle, tag: String) -> Result<Option<String>, AppError> {
+    let tag = tag.trim();
+    let locale = match tag {
+        "" => None,
+        tag if i18n::is_valid_tag(tag) => Some(tag.to_string()),
+        tag => return Err(AppError::InvalidInput(format!("Invalid locale tag: {}", tag))),
+    };
+    let mut config = config::load(&app_handle);
+    config.locale = locale.clone();
+    config::save(&app_handle, &config)?;
+    i18n::set_override(locale);
+    Ok(i18n::current_locale())
 }
 
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+// 开启或关闭 command-timing 事件，立即对之后的命令生效
 #[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
+fn set_timing_enabled(app_handle: tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    let mut config = config::load(&app_handle);
+    config.timing_enabled = enabled;
+    config::save(&app_handle, &config)?;
+    logging::set_timing_enabled(enabled);
+    Ok(())
 }
 
+// 把已完成的任务移入 tasks_archive.json，返回归档数量
 #[tauri::command]
-fn save_notes(app_handle: tauri::AppHandle, notes: String) -> Result<(), String> {
-    let app_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    
-    // 确保目录存在
-    fs::create_dir_all(&app_dir).map_err(|e| format!("Failed to create app directory: {}", e))?;
-    
+fn archive_completed_tasks(app_handle: tauri::AppHandle) -> Result<usize, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    modify_tasks(&app_handle, |tasks| {
+        let (completed, active): (Vec<_>, Vec<_>) =
+            tasks.drain(..).partition(|task| task.completed);
+        *tasks = active;
+        let count = completed.len();
+        // 先追加归档再写回任务：中途失败时宁可重复也不丢失
+        if count > 0 {
+            archive::append(&app_dir, completed)?;
+        }
+        Ok(count)
+    })
+}
+
+#[tauri::command]
+fn load_archived_tasks(app_handle: tauri::AppHandle) -> Result<Vec<archive::ArchivedTask>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    archive::load(&app_dir)
+}
+
+#[tauri::command]
+fn get_overdue_tasks(app_handle: tauri::AppHandle) -> Result<Vec<tasks::Task>, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let tasks = tasks::parse(&read_tasks_json(&app_handle, &app_dir)?)?;
+    Ok(tasks::overdue(tasks))
+}
+
+#[tauri::command]
+fn set_reminder_lead_minutes(app_handle: tauri::AppHandle, n: u32) -> Result<(), AppError> {
+    let mut config = config::load(&app_handle);
+    config.reminder_lead_minutes = Some(n);
+    config::save(&app_handle, &config)
+}
+
+// 切换存储后端："json"（默认）、"sqlite"、"files"（每条笔记一个文件）
+// 或 "markdown"（每条笔记一个带 front matter 的 .md 文件）。
+// 切换时把当前后端中的数据写入新后端；切到其它后端后 notes.json 重命名为 .migrated
+#[tauri::command]
+fn set_storage_backend(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    backend: String,
+) -> Result<(), AppError> {
+    ensure_editable(&app_handle)?;
+    let backend = match backend.as_str() {
+        "json" => config::StorageBackend::Json,
+        "sqlite" => config::StorageBackend::Sqlite,
+        "files" => config::StorageBackend::Files,
+        "markdown" => config::StorageBackend::Markdown,
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown storage backend: {}",
+                backend
+            )))
+        }
+    };
+
+    let mut config = config::load(&app_handle);
+    if config.storage_backend == backend {
+        return Ok(());
+    }
+    if backend != config::StorageBackend::Json && config.encryption_enabled {
+        return Err(AppError::InvalidInput(
+            "Encrypted notes can only be stored in notes.json".to_string(),
+        ));
+    }
+
+    let _guard = save_queue::lock(&app_handle);
+    let app_dir = storage_dir(&app_handle)?;
+    // 按存储形式迁移：secure 笔记保持原有密文，未解锁时同样可以切换
+    let notes = read_stored_notes_json(&app_handle, &app_dir, &passphrase)?;
+    let tasks: serde_json::Value = serde_json::from_str(&read_tasks_json(&app_handle, &app_dir)?)
+        .map_err(AppError::serialization("Failed to parse tasks"))?;
+
+    let previous = config.clone();
+    config.storage_backend = backend;
+    config::save(&app_handle, &config)?;
+    // 写入操作按新配置分派到新后端；打开数据库时会先导入并重命名现有 JSON 文件。
+    // 写入失败时恢复原配置，避免之后指向一个空的新后端
+    let written = write_notes_json_to_backend(&app_handle, &app_dir, &passphrase, &notes)
+        .and_then(|_| write_tasks_json(&app_handle, &app_dir, &tasks));
+    if let Err(e) = written {
+        if let Err(rollback) = config::save(&app_handle, &previous) {
+            tracing::error!("Failed to restore storage backend: {}", rollback);
+        }
+        return Err(e);
+    }
+
     let notes_file = app_dir.join("notes.json");
-    fs::write(notes_file, notes).map_err(|e| format!("Failed to save notes: {}", e))?;
-    
+    let per_note_files =
+        matches!(backend, config::StorageBackend::Files | config::StorageBackend::Markdown);
+    if per_note_files && notes_file.exists() {
+        fs::rename(&notes_file, app_dir.join("notes.json.migrated"))
+            .map_err(AppError::io("Failed to rename migrated file"))?;
+    }
     Ok(())
 }
 
+// 新建笔记并追加保存；id 由后端生成并保证与已有笔记不重复
+fn insert_new_note(
+    app_handle: &tauri::AppHandle,
+    passphrase: &crypto::PassphraseState,
+    title: String,
+    body: String,
+) -> Result<notes::Note, AppError> {
+    // modify_notes 会先写入尚未落盘的防抖内容，避免其随后覆盖新笔记
+    let note = modify_notes(app_handle, passphrase, |notes| {
+        let note = notes::new_unique(notes, title, body);
+        notes.push(note.clone());
+        Ok(note)
+    })?;
+    metrics::increment(app_handle, metrics::Counter::NotesCreated);
+    Ok(note)
+}
+
+// 由后端分配 id 与创建、修改时间新建笔记并保存，返回完整的笔记；
+// 前端不再自行生成 id，避免导入后出现重复 id
 #[tauri::command]
-fn load_notes(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let app_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    
-    let notes_file = app_dir.join("notes.json");
-    
-    if notes_file.exists() {
-        fs::read_to_string(notes_file).map_err(|e| format!("Failed to load notes: {}", e))
+fn new_note(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    title: String,
+    body: String,
+) -> Result<notes::Note, AppError> {
+    ensure_editable(&app_handle)?;
+    insert_new_note(&app_handle, &passphrase, title, body)
+}
+
+// 以剪贴板中的文本新建笔记并保存，返回新笔记的 id
+#[tauri::command]
+fn note_from_clipboard(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    clipboard::create_note(&app_handle)
+}
+
+// 把单条笔记弹出为独立的小窗口；已弹出时聚焦已有窗口
+#[tauri::command]
+fn open_note_window(app_handle: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    note_windows::open(&app_handle, &id)
+}
+
+// 保存单条笔记（新增或按 id 覆盖），返回笔记 id；
+// files 后端只重写该笔记的文件（secure 笔记除外），其它后端整体读写
+#[tauri::command]
+fn save_note(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+    note: notes::Note,
+) -> Result<String, AppError> {
+    ensure_editable(&app_handle)?;
+    tags::validate(std::slice::from_ref(&note))?;
+    let app_dir = storage_dir(&app_handle)?;
+    // 没有 id 的是新笔记，与 insert_new_note 一样由后端分配不重复的 id
+    let created = note.id.is_empty();
+    // secure 笔记需要经由 write_notes_json 加密正文
+    let id = if config::load(&app_handle).storage_backend == config::StorageBackend::Files
+        && !note.secure
+    {
+        autosave::flush(&app_handle)?;
+        let _guard = save_queue::lock(&app_handle);
+        offline::probe(&app_handle)?;
+        // 读取全部笔记只用于分配 id、计算修改次数与大小检查，写入时仍只重写这一个文件
+        let previous = note_files::load_notes(&app_dir);
+        let mut notes = previous.as_ref().map_or_else(|_| Vec::new(), Clone::clone);
+        let id = upsert_note(&mut notes, note, created);
+        write_notes_with(&app_handle, &app_dir, previous, &mut notes, |notes| {
+            note_files::save_note(&app_dir, notes::find(notes, &id)?.clone()).map(|_| ())
+        })?;
+        id
     } else {
-        Ok("[]".to_string()) // 返回空数组
+        modify_notes(&app_handle, &passphrase, |notes| Ok(upsert_note(notes, note, created)))?
+    };
+    if created {
+        metrics::increment(&app_handle, metrics::Counter::NotesCreated);
+    }
+    Ok(id)
+}
+
+// 按 id 覆盖已有笔记，不存在时追加；created 为 true 时先分配不重复的 id。返回笔记 id
+fn upsert_note(notes: &mut Vec<notes::Note>, mut note: notes::Note, created: bool) -> String {
+    if created {
+        note.id = notes::unique_id(notes);
+    }
+    let id = note.id.clone();
+    match notes.iter_mut().find(|existing| existing.id == note.id) {
+        Some(existing) => *existing = note,
+        None => notes.push(note),
     }
+    id
 }
 
+// 把现有数据迁移到新的存储目录：全部复制并校验后才切换配置；
+// delete_originals 为 true 时最后删除原目录中的数据
 #[tauri::command]
-fn save_tasks(app_handle: tauri::AppHandle, payload: TasksPayload) -> Result<(), String> {
-    let app_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    
-    // 确保目录存在
-    fs::create_dir_all(&app_dir).map_err(|e| format!("Failed to create app directory: {}", e))?;
-    
-    let tasks_file = app_dir.join("tasks.json");
-    let tasks_json = serde_json::to_string(&payload.tasks)
-        .map_err(|e| format!("Failed to serialize tasks: {}", e))?;
-    fs::write(tasks_file, tasks_json).map_err(|e| format!("Failed to save tasks: {}", e))?;
-    
+fn migrate_storage_to(
+    app_handle: tauri::AppHandle,
+    new_path: String,
+    delete_originals: Option<bool>,
+) -> Result<(), AppError> {
+    let to = PathBuf::from(&new_path);
+    fs::create_dir_all(&to).map_err(AppError::io("Failed to create storage directory"))?;
+    probe_writable(&to).map_err(AppError::io("Storage path is not writable"))?;
+    let from = storage_dir(&app_handle)?;
+    if fs::canonicalize(&from).ok() == fs::canonicalize(&to).ok() {
+        return Err(AppError::InvalidInput(
+            "Storage path is already the current directory".to_string(),
+        ));
+    }
+
+    // 先写入尚未落盘的防抖内容，确保迁移的是最新数据
+    autosave::flush(&app_handle)?;
+    // 复制与切换配置期间暂停保存，避免写入已复制过的原目录
+    let _guard = save_queue::lock(&app_handle);
+    let files = relocate::copy_all(&from, &to)?;
+
+    let mut config = config::load(&app_handle);
+    config.storage_path = Some(new_path);
+    config::save(&app_handle, &config)?;
+    fallback::check(&app_handle)?;
+    rewatch_storage_dir(&app_handle);
+
+    if delete_originals.unwrap_or(false) {
+        relocate::remove_originals(&from, &files);
+    }
     Ok(())
 }
 
 #[tauri::command]
-fn load_tasks(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let app_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    
-    let tasks_file = app_dir.join("tasks.json");
-    
-    if tasks_file.exists() {
-        fs::read_to_string(tasks_file).map_err(|e| format!("Failed to load tasks: {}", e))
+fn set_storage_path(app_handle: tauri::AppHandle, path: String) -> Result<(), AppError> {
+    let mut config = config::load(&app_handle);
+
+    // 传入空路径表示恢复默认目录
+    if path.trim().is_empty() {
+        config.storage_path = None;
+        config::save(&app_handle, &config)?;
+        // 默认目录可能仍不可写，重新检测是否需要使用临时目录
+        fallback::check(&app_handle)?;
+        rewatch_storage_dir(&app_handle);
+        return Ok(());
+    }
+
+    let dir = PathBuf::from(&path);
+    if !dir.is_dir() {
+        return Err(AppError::InvalidInput(format!(
+            "Storage path is not an existing directory: {}",
+            path
+        )));
+    }
+    probe_writable(&dir).map_err(AppError::io("Storage path is not writable"))?;
+
+    config.storage_path = Some(path);
+    config::save(&app_handle, &config)?;
+    // 新目录已确认可写，不再使用临时目录
+    fallback::check(&app_handle)?;
+    rewatch_storage_dir(&app_handle);
+    Ok(())
+}
+
+// 配置已保存，监听切换失败只影响外部修改提醒
+fn rewatch_storage_dir(app_handle: &tauri::AppHandle) {
+    if let Err(e) = watcher::watch_storage_dir(app_handle) {
+        tracing::warn!("Failed to watch storage directory: {}", e);
+    }
+}
+
+// 文件不存在时大小记为 0
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+#[tauri::command]
+fn get_storage_stats(
+    app_handle: tauri::AppHandle,
+    passphrase: tauri::State<'_, crypto::PassphraseState>,
+) -> Result<StorageStats, AppError> {
+    let app_dir = storage_dir(&app_handle)?;
+    let note_count = match read_notes_json(&app_handle, &app_dir, &passphrase) {
+        Ok(notes) => Some(notes::parse(&notes)?.len()),
+        Err(AppError::Locked) => None,
+        Err(e) => return Err(e),
+    };
+    let task_count = tasks::parse(&read_tasks_json(&app_handle, &app_dir)?)?.len();
+    let backup_count = backup::list(&app_dir)
+        .map_err(AppError::io("Failed to list backups"))?
+        .len();
+
+    Ok(StorageStats {
+        storage_dir: app_dir.to_string_lossy().into_owned(),
+        notes_bytes: file_size(&app_dir.join("notes.json")),
+        tasks_bytes: file_size(&app_dir.join("tasks.json")),
+        database_bytes: file_size(&app_dir.join(sqlite::DB_FILE)),
+        note_count,
+        task_count,
+        backup_count,
+    })
+}
+
+// 冷启动时由链接触发的操作结果，前端加载完成后调用一次
+#[tauri::command]
+fn take_pending_deep_links(app_handle: tauri::AppHandle) -> Vec<deep_link::DeepLinkEvent> {
+    deep_link::take_pending(&app_handle)
+}
+
+#[tauri::command]
+fn get_app_info(app_handle: tauri::AppHandle) -> AppInfo {
+    let package_info = app_handle.package_info();
+    AppInfo {
+        name: package_info.name.clone(),
+        version: package_info.version.to_string(),
+        tauri_version: tauri::VERSION,
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        git_commit: option_env!("STICKY_GIT_COMMIT"),
+        portable: portable::data_dir().is_some(),
+    }
+}
+
+// 最新日志文件的路径，便于用户附在问题反馈中
+#[tauri::command]
+fn get_log_path(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    logging::latest_log_file(&app_handle).map(|path| path.to_string_lossy().into_owned())
+}
+
+// 本地 HTTP API 的令牌，请求时放在 X-Api-Token 请求头中
+#[tauri::command]
+fn get_api_token(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    api::token(&app_handle)
+}
+
+#[tauri::command]
+fn get_storage_path(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    storage_dir(&app_handle).map(|dir| dir.to_string_lossy().into_owned())
+}
+
+// 在系统文件管理器中打开数据存储目录，目录不存在时先创建
+#[tauri::command]
+fn reveal_storage_dir(app_handle: tauri::AppHandle) -> Result<(), AppError> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let dir = storage_dir(&app_handle)?;
+    fs::create_dir_all(&dir).map_err(AppError::io("Failed to create app directory"))?;
+    app_handle
+        .opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(AppError::platform("Failed to open storage directory"))
+}
+
+// 把配置中的置顶、任务栏与贴靠设置应用到主窗口；未设置的项保持窗口当前状态
+fn apply_window_config(window: &tauri::WebviewWindow, config: &config::AppConfig) {
+    if let Some(enabled) = config.always_on_top {
+        let _ = window.set_always_on_top(enabled);
+    }
+    if let Some(enabled) = config.skip_taskbar {
+        let _ = window.set_skip_taskbar(enabled);
+    }
+    if let Some(corner) = config.window_anchor {
+        if let Err(e) = window_anchor::apply(&window.as_ref().window(), corner) {
+            tracing::warn!("Failed to anchor window: {}", e);
+        }
+    }
+}
+
+// 手动修改 config.json / shortcuts.json 后重新加载并应用，载荷为重新读取的配置
+const CONFIG_RELOADED_EVENT: &str = "config-reloaded";
+
+// 用默认编辑器打开 config.json，文件不存在时先写入默认配置
+#[tauri::command]
+fn open_config_file(app_handle: tauri::AppHandle) -> Result<(), AppError> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let path = config::config_file(&app_handle)?;
+    if !path.exists() {
+        config::save(&app_handle, &config::AppConfig::default())?;
+    }
+    app_handle
+        .opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(AppError::platform("Failed to open config file"))
+}
+
+// 无需重启即可应用手动修改的配置：存储目录、快捷键、窗口设置、命令计时与语言；
+// 备份保留数量、提醒提前量等设置每次使用时读取，自动生效。文件无法解析时不做任何改动
+#[tauri::command]
+fn reload_config(app_handle: tauri::AppHandle) -> Result<(), AppError> {
+    let config = config::load_strict(&app_handle)?;
+
+    fallback::check(&app_handle)?;
+    rewatch_storage_dir(&app_handle);
+    shortcuts::reload(&app_handle)?;
+    if let Some(window) = app_handle.get_webview_window("main") {
+        apply_window_config(&window, &config);
+        if let Some(level) = config.window_opacity {
+            apply_window_opacity(&window, level)?;
+        }
+    }
+    logging::set_timing_enabled(config.timing_enabled);
+    i18n::set_override(config.locale.clone());
+
+    app_handle.emit(CONFIG_RELOADED_EVENT, config)?;
+    Ok(())
+}
+
+#[tauri::command]
+fn set_autostart(app_handle: tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    use tauri_plugin_autostart::ManagerExt;
+
+    let autolaunch = app_handle.autolaunch();
+    let result = if enabled {
+        autolaunch.enable()
     } else {
-        Ok("[]".to_string()) // 返回空数组
+        autolaunch.disable()
+    };
+    result.map_err(AppError::platform("Failed to update autostart"))
+}
+
+#[tauri::command]
+fn get_autostart(app_handle: tauri::AppHandle) -> Result<bool, AppError> {
+    use tauri_plugin_autostart::ManagerExt;
+
+    app_handle
+        .autolaunch()
+        .is_enabled()
+        .map_err(AppError::platform("Failed to read autostart state"))
+}
+
+// 本次启动的来源："user"（手动启动）或 "autostart"（开机自启）
+#[tauri::command]
+fn get_launch_reason() -> String {
+    launch::reason().as_str().to_string()
+}
+
+// 显示并聚焦主窗口（包括从最小化恢复）
+fn show_main_window(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+// 设置主窗口置顶并持久化，启动时在 setup 中重新应用
+fn apply_always_on_top(app_handle: &tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    let window = app_handle
+        .get_webview_window("main")
+        .ok_or_else(|| AppError::NotFound("Main window not found".to_string()))?;
+    window
+        .set_always_on_top(enabled)
+        .map_err(AppError::platform("Failed to set always on top"))?;
+
+    let mut config = config::load(app_handle);
+    config.always_on_top = Some(enabled);
+    config::save(app_handle, &config)
+}
+
+fn toggle_always_on_top(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let window = app_handle
+        .get_webview_window("main")
+        .ok_or_else(|| AppError::NotFound("Main window not found".to_string()))?;
+    let enabled = window
+        .is_always_on_top()
+        .map_err(AppError::platform("Failed to read always on top"))?;
+    apply_always_on_top(app_handle, !enabled)
+}
+
+#[tauri::command]
+fn set_always_on_top(app_handle: tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    apply_always_on_top(&app_handle, enabled)
+}
+
+// 在任务栏中隐藏主窗口并持久化，与置顶配合即为常驻桌面的小组件模式；仅 Windows 与 Linux 支持
+fn apply_skip_taskbar(app_handle: &tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    if cfg!(target_os = "macos") {
+        return Err(AppError::Platform(
+            "Hiding from the taskbar is not supported on macOS".to_string(),
+        ));
+    }
+    let window = app_handle
+        .get_webview_window("main")
+        .ok_or_else(|| AppError::NotFound("Main window not found".to_string()))?;
+    window
+        .set_skip_taskbar(enabled)
+        .map_err(AppError::platform("Failed to set skip taskbar"))?;
+
+    let mut config = config::load(app_handle);
+    config.skip_taskbar = Some(enabled);
+    config::save(app_handle, &config)
+}
+
+#[tauri::command]
+fn set_skip_taskbar(app_handle: tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    apply_skip_taskbar(&app_handle, enabled)
+}
+
+const MIN_WINDOW_OPACITY: f64 = 0.2;
+const MAX_WINDOW_OPACITY: f64 = 1.0;
+
+// 窗口本身是透明的，通过页面根元素的 opacity 实现整体半透明
+fn apply_window_opacity(window: &tauri::WebviewWindow, level: f64) -> Result<(), AppError> {
+    window
+        .eval(&format!("document.documentElement.style.opacity = '{}'", level))
+        .map_err(AppError::platform("Window opacity is not supported"))
+}
+
+#[tauri::command]
+fn set_window_opacity(app_handle: tauri::AppHandle, level: f64) -> Result<(), AppError> {
+    if level.is_nan() {
+        return Err(AppError::InvalidInput("Opacity must be a number".to_string()));
+    }
+    let level = level.clamp(MIN_WINDOW_OPACITY, MAX_WINDOW_OPACITY);
+
+    let window = app_handle
+        .get_webview_window("main")
+        .ok_or_else(|| AppError::NotFound("Main window not found".to_string()))?;
+    apply_window_opacity(&window, level)?;
+
+    let mut config = config::load(&app_handle);
+    config.window_opacity = Some(level);
+    config::save(&app_handle, &config)
+}
+
+// 记录当前聚焦的笔记，下次启动时在 storage-ready 之后通过 restore-active-note 恢复
+#[tauri::command]
+fn set_active_note(app_handle: tauri::AppHandle, id: String) -> Result<(), AppError> {
+    let mut config = config::load(&app_handle);
+    config.active_note = Some(id);
+    config::save(&app_handle, &config)
+}
+
+// 把主窗口贴到当前显示器的某个角落（top_left、top_right、bottom_left、bottom_right）并记住，
+// 启动及移到其它显示器时重新贴靠；传入 "none" 取消贴靠
+#[tauri::command]
+fn set_window_anchor(app_handle: tauri::AppHandle, corner: String) -> Result<(), AppError> {
+    let corner = match corner.as_str() {
+        "none" => None,
+        corner => Some(window_anchor::Corner::parse(corner)?),
+    };
+    if let Some(corner) = corner {
+        let window = app_handle
+            .get_webview_window("main")
+            .ok_or_else(|| AppError::NotFound("Main window not found".to_string()))?;
+        window_anchor::apply(&window.as_ref().window(), corner)?;
+    }
+
+    let mut config = config::load(&app_handle);
+    config.window_anchor = corner;
+    config::save(&app_handle, &config)
+}
+
+#[tauri::command]
+fn set_shortcut(app_handle: tauri::AppHandle, action: String, accelerator: String) -> Result<(), AppError> {
+    shortcuts::set(&app_handle, &action, &accelerator)
+}
+
+// 快捷键帮助面板：每个动作的当前快捷键与说明，set_shortcut 修改后立即反映
+#[tauri::command]
+fn list_shortcuts(app_handle: tauri::AppHandle) -> Vec<shortcuts::ShortcutInfo> {
+    shortcuts::list(&app_handle)
+}
+
+// 切换主窗口的显示/隐藏
+fn toggle_main_window(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        match window.is_visible() {
+            Ok(true) => {
+                let _ = window.hide();
+            }
+            Ok(false) => {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            Err(_) => {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+    }
+}
+
+const FOCUS_NEW_NOTE_EVENT: &str = "focus-new-note";
+
+// 新建空白笔记并保存，随后显示主窗口（隐藏时同样生效），
+// 并发出 focus-new-note 以便前端把光标放入新笔记
+fn create_blank_note(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    ensure_editable(app_handle)?;
+    let passphrase = app_handle.state::<crypto::PassphraseState>();
+    let note = insert_new_note(app_handle, &passphrase, String::new(), String::new())?;
+    watcher::notify_changed(app_handle, "notes.json");
+
+    show_main_window(app_handle);
+    app_handle.emit_to("main", FOCUS_NEW_NOTE_EVENT, &note.id)?;
+    Ok(())
+}
+
+// 优雅关闭应用：等待前端完成最后一次保存后退出
+fn quit_app(app_handle: &tauri::AppHandle) {
+    shutdown::begin(app_handle, |app_handle| app_handle.exit(0));
+}
+
+// Ctrl+Q：按 quit_behavior 退出应用或把主窗口隐藏到托盘
+fn quit_or_hide(app_handle: &tauri::AppHandle) {
+    match config::load(app_handle).quit_behavior {
+        config::QuitBehavior::Exit => quit_app(app_handle),
+        config::QuitBehavior::MinimizeToTray => {
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.hide();
+            }
+        }
     }
 }
 
+// 设置 Ctrl+Q 与关闭按钮的行为："exit"（默认，退出应用）或 "minimize_to_tray"（隐藏到托盘）
+#[tauri::command]
+fn set_quit_behavior(app_handle: tauri::AppHandle, mode: String) -> Result<(), AppError> {
+    let behavior = match mode.as_str() {
+        "exit" => config::QuitBehavior::Exit,
+        "minimize_to_tray" => config::QuitBehavior::MinimizeToTray,
+        _ => return Err(AppError::InvalidInput(format!("Unknown quit behavior: {}", mode))),
+    };
+    let mut config = config::load(&app_handle);
+    config.quit_behavior = behavior;
+    config::save(&app_handle, &config)
+}
+
+// 前端收到 flush-before-close 并完成保存后调用
+#[tauri::command]
+fn close_ready(app_handle: tauri::AppHandle) {
+    shutdown::acknowledge(&app_handle);
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // 必须最先注册：再次启动时聚焦已有窗口而不是启动第二个进程，避免两个进程同时写 notes.json
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            show_main_window(app);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        // 开机自启时附带 --autostart 参数，便于区分启动来源
+        .plugin(tauri_plugin_autostart::init(
+            tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+            Some(vec![launch::AUTOSTART_ARG]),
+        ))
+        .manage(crypto::PassphraseState::default())
+        .manage(shortcuts::Bindings::default())
+        .manage(autosave::PendingNotes::default())
+        .manage(watcher::FileWatcher::default())
+        .manage(shutdown::CloseState::default())
+        .manage(deep_link::PendingDeepLinks::default())
+        .manage(history::NotesHistory::default())
+        .manage(fallback::StorageFallback::default())
+        .manage(storage_ready::StorageReady::default())
+        .manage(save_queue::SaveQueue::default())
+        .manage(offline::StorageOffline::default())
         .setup(|app| {
-            #[cfg(desktop)]
+            // 最先初始化日志，之后各步骤的失败都能写入日志文件
+            match logging::init(app.handle()) {
+                Ok(guard) => {
+                    app.manage(guard);
+                }
+                Err(e) => eprintln!("Failed to initialize logging: {}", e),
+            }
+            if let Some(dir) = data_override::data_dir() {
+                let source = data_override::ENV_VAR;
+                tracing::info!("Using data directory {} from {}", dir.display(), source);
+            }
+            metrics::increment(app.handle(), metrics::Counter::Launches);
+            let launch_reason = launch::reason();
+            tracing::info!("Launched by {}", launch_reason.as_str());
+            let config = config::load(app.handle());
+            logging::set_timing_enabled(config.timing_enabled);
+            i18n::set_override(config.locale.clone());
+
+            // 窗口在配置中默认隐藏，恢复上次的位置与尺寸后再显示，避免启动时跳动；
+            // 开机自启时保持隐藏，只显示托盘图标
+            if let Some(window) = app.get_webview_window("main") {
+                if let Err(e) = window_state::restore(&window) {
+                    tracing::warn!("Failed to restore window state: {}", e);
+                }
+                apply_window_config(&window, &config);
+                if launch_reason == launch::LaunchReason::User {
+                    let _ = window.show();
+                }
+            }
+
+            // 在任何读写之前确认数据目录可写，否则改用临时目录
+            if let Err(e) = fallback::check(app.handle()) {
+                tracing::error!("Failed to check storage directory: {}", e);
+            }
+            if let Err(e) = storage_ready::prepare(app.handle()) {
+                tracing::error!("Failed to prepare storage directory: {}", e);
+            }
+
+            note_windows::restore(app.handle());
+
+            if let Err(e) = purge_expired_trash(app.handle()) {
+                tracing::warn!("Failed to purge trash: {}", e);
+            }
+            reminders::start(app.handle().clone());
+            scheduled_backup::start(app.handle().clone());
+            // sticky:// 链接：Linux 与 Windows 开发环境需要在运行时注册协议，
+            // 已运行时再次点击链接会经由 single-instance 插件转发到 on_open_url
             {
-                use tauri_plugin_global_shortcut::{Code, Modifiers, ShortcutState};
+                use tauri_plugin_deep_link::DeepLinkExt;
+
+                #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+                if let Err(e) = app.deep_link().register_all() {
+                    tracing::warn!("Failed to register deep link scheme: {}", e);
+                }
+                if let Ok(Some(urls)) = app.deep_link().get_current() {
+                    deep_link::handle(app.handle(), urls, true);
+                }
                 let app_handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    deep_link::handle(&app_handle, event.urls(), false);
+                });
+            }
+
+            // 供脚本读写笔记的本地 HTTP API，端口被占用等失败不影响应用启动
+            if let Err(e) = api::start(app.handle().clone()) {
+                tracing::warn!("Failed to start HTTP API: {}", e);
+            }
+            // 同步文件夹中的数据可能被其它设备改写，通知前端重新加载
+            if let Err(e) = watcher::start(app.handle()) {
+                tracing::warn!("Failed to start file watcher: {}", e);
+            }
+
+            // 全局快捷键从 shortcuts.json 读取，可通过 set_shortcut 修改
+            #[cfg(desktop)]
+            {
+                use tauri_plugin_global_shortcut::ShortcutState;
                 
                 app.handle().plugin(
                     tauri_plugin_global_shortcut::Builder::new()
-                        .with_shortcuts(["CmdOrCtrl+M", "CmdOrCtrl+Q"])?
-                        .with_handler(move |app, shortcut, event| {
-                            if event.state == ShortcutState::Pressed {
-                                if shortcut.matches(Modifiers::CONTROL, Code::KeyM) || 
-                                   shortcut.matches(Modifiers::META, Code::KeyM) {
-                                    if let Some(window) = app_handle.get_webview_window("main") {
-                                        match window.is_visible() {
-                                            Ok(true) => {
-                                                let _ = window.hide();
-                                            }
-                                            Ok(false) => {
-                                                let _ = window.show();
-                                                let _ = window.set_focus();
-                                            }
-                                            Err(_) => {
-                                                let _ = window.show();
-                                                let _ = window.set_focus();
-                                            }
-                                        }
+                        .with_handler(|app, shortcut, event| {
+                            if event.state != ShortcutState::Pressed {
+                                return;
+                            }
+                            match shortcuts::action_for(app, shortcut).as_deref() {
+                                Some(shortcuts::TOGGLE_WINDOW) => toggle_main_window(app),
+                                // 优雅关闭应用
+                                Some(shortcuts::QUIT) => quit_or_hide(app),
+                                Some(shortcuts::TOGGLE_ALWAYS_ON_TOP) => {
+                                    if let Err(e) = toggle_always_on_top(app) {
+                                        tracing::warn!("Failed to toggle always on top: {}", e);
+                                    }
+                                }
+                                Some(shortcuts::NOTE_FROM_CLIPBOARD) => {
+                                    if let Err(e) = clipboard::create_note(app) {
+                                        tracing::warn!("Failed to create note from clipboard: {}", e);
+                                    }
+                                }
+                                Some(shortcuts::NEW_NOTE) => {
+                                    if let Err(e) = create_blank_note(app) {
+                                        tracing::warn!("Failed to create note: {}", e);
                                     }
-                                } else if shortcut.matches(Modifiers::CONTROL, Code::KeyQ) || 
-                                          shortcut.matches(Modifiers::META, Code::KeyQ) {
-                                    // Ctrl+Q 优雅关闭应用
-                                    let app_handle = app_handle.clone();
-                                    std::thread::spawn(move || {
-                                        // 给前端一点时间完成任何正在进行的保存操作
-                                        std::thread::sleep(std::time::Duration::from_millis(100));
-                                        app_handle.exit(0);
-                                    });
                                 }
+                                _ => {}
                             }
                         })
                         .build(),
                 )?;
+                shortcuts::register_all(app.handle())?;
+            }
+
+            // 系统托盘：左键单击切换窗口，右键菜单提供显示/隐藏与退出
+            #[cfg(desktop)]
+            {
+                use tauri::menu::{Menu, MenuItem};
+                use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+
+                let toggle_item = MenuItem::with_id(app, "toggle", "显示/隐藏", true, None::<&str>)?;
+                let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
+                let menu = Menu::with_items(app, &[&toggle_item, &quit_item])?;
+
+                let mut tray = TrayIconBuilder::with_id("main")
+                    .tooltip("任务清单")
+                    .menu(&menu)
+                    .show_menu_on_left_click(false)
+                    .on_menu_event(|app, event| match event.id.as_ref() {
+                        "toggle" => toggle_main_window(app),
+                        "quit" => quit_app(app),
+                        _ => {}
+                    })
+                    .on_tray_icon_event(|tray, event| {
+                        if let TrayIconEvent::Click {
+                            button: MouseButton::Left,
+                            button_state: MouseButtonState::Up,
+                            ..
+                        } = event
+                        {
+                            toggle_main_window(tray.app_handle());
+                        }
+                    });
+                if let Some(icon) = app.default_window_icon() {
+                    tray = tray.icon(icon.clone());
+                }
+                tray.build(app)?;
+            }
+
+            if let Err(e) = badge::refresh(app.handle()) {
+                tracing::warn!("Failed to update task badge: {}", e);
             }
             
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![greet, save_notes, load_notes, save_tasks, load_tasks])
+        // 页面加载完成（包括刷新）后重新应用保存的不透明度
+        .on_page_load(|webview, payload| {
+            if webview.label() != "main"
+                || !matches!(payload.event(), tauri::webview::PageLoadEvent::Finished)
+            {
+                return;
+            }
+            let app_handle = webview.app_handle();
+            fallback::notify(app_handle);
+            storage_ready::notify(app_handle);
+            if let (Some(level), Some(window)) = (
+                config::load(app_handle).window_opacity,
+                app_handle.get_webview_window("main"),
+            ) {
+                if let Err(e) = apply_window_opacity(&window, level) {
+                    tracing::warn!("Failed to apply window opacity: {}", e);
+                }
+            }
+        })
+        .on_window_event(|window, event| {
+            if note_windows::is_note_window(window.label()) {
+                if let tauri::WindowEvent::CloseRequested { .. } = event {
+                    if let Err(e) = note_windows::forget(window.app_handle(), window.label()) {
+                        tracing::warn!("Failed to save note windows: {}", e);
+                    }
+                }
+                return;
+            }
+            if window.label() != "main" {
+                return;
+            }
+            match event {
+                tauri::WindowEvent::Moved(_) => {
+                    if let Err(e) = window_anchor::on_moved(window) {
+                        tracing::warn!("Failed to anchor window: {}", e);
+                    }
+                    if let Err(e) = window_state::save(window) {
+                        tracing::warn!("Failed to save window state: {}", e);
+                    }
+                }
+                tauri::WindowEvent::Resized(_) => {
+                    if let Err(e) = window_state::save(window) {
+                        tracing::warn!("Failed to save window state: {}", e);
+                    }
+                }
+                tauri::WindowEvent::CloseRequested { api, .. } => {
+                    if shutdown::is_ready(window.app_handle()) {
+                        return;
+                    }
+                    if let Err(e) = window_state::save(window) {
+                        tracing::warn!("Failed to save window state: {}", e);
+                    }
+                    api.prevent_close();
+                    // 托盘模式下只隐藏窗口，进程继续运行
+                    if config::load(window.app_handle()).quit_behavior
+                        == config::QuitBehavior::MinimizeToTray
+                    {
+                        let _ = window.hide();
+                        return;
+                    }
+                    // 等前端确认最后一次保存（或超时）后再真正关闭
+                    let app_handle = window.app_handle().clone();
+                    let window = window.clone();
+                    shutdown::begin(&app_handle, move |_| {
+                        if let Err(e) = window.close() {
+                            tracing::warn!("Failed to close window: {}", e);
+                        }
+                    });
+                }
+                _ => {}
+            }
+        })
+        .invoke_handler(logging::with_command_log(tauri::generate_handler![
+            self_check,
+            save_notes,
+            save_notes_debounced,
+            undo_notes,
+            redo_notes,
+            save_note,
+            open_note_window,
+            new_note,
+            note_from_clipboard,
+            load_notes,
+            load_notes_with_recovery,
+            export_markdown,
+            export_note_html,
+            print_note_pdf,
+            import_markdown,
+            search_notes,
+            notes_modified_between,
+            note_stats,
+            render_note_html,
+            filter_notes_by_tag,
+            list_all_tags,
+            set_note_color,
+            set_note_reminder,
+            clear_note_reminder,
+            attach_image,
+            remove_attachment,
+            reorder_note,
+            set_note_secure,
+            unlock_secure_notes,
+            set_note_pinned,
+            load_notes_sorted,
+            get_recent_notes,
+            most_edited_notes,
+            delete_note,
+            trim_empty_notes,
+            run_integrity_scan,
+            find_duplicate_notes,
+            merge_notes,
+            list_trash,
+            restore_note,
+            empty_trash,
+            list_backups,
+            restore_backup,
+            diff_backups,
+            export_archive,
+            import_archive,
+            set_backup_retention,
+            prune_backups,
+            compact_backups,
+            set_backup_interval_hours,
+            save_tasks,
+            save_all,
+            load_tasks,
+            load_tasks_by_priority,
+            search_tasks,
+            refresh_task_badge,
+            export_tasks_csv,
+            import_todoist,
+            repair_tasks,
+            dedupe_tasks,
+            bulk_update_tasks,
+            get_overdue_tasks,
+            complete_task,
+            set_metrics_enabled,
+            get_metrics,
+            reset_metrics,
+            set_timing_enabled,
+            set_locale,
+            archive_completed_tasks,
+            load_archived_tasks,
+            set_reminder_lead_minutes,
+            set_storage_backend,
+            set_storage_path,
+            migrate_storage_to,
+            get_storage_path,
+            reveal_storage_dir,
+            open_config_file,
+            reload_config,
+            get_storage_stats,
+            get_log_path,
+            get_app_info,
+            take_pending_deep_links,
+            get_api_token,
+            set_passphrase,
+            set_autostart,
+            get_autostart,
+            get_launch_reason,
+            set_always_on_top,
+            set_readonly,
+            get_readonly,
+            set_pretty_json,
+            set_size_limits,
+            set_skip_taskbar,
+            set_window_opacity,
+            set_window_anchor,
+            set_active_note,
+            set_shortcut,
+            list_shortcuts,
+            set_quit_behavior,
+            close_ready
+        ]))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 每个测试使用独立的临时目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("sticky-note-{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn interrupted_write_keeps_original_file() {
+        let dir = temp_dir("write-atomic");
+        let notes_file = dir.join("notes.json");
+        let original =
+            notes::to_envelope(r#"[{"id":"a","title":"keep","body":"me"}]"#, false).unwrap();
+        fs::write(&notes_file, &original).unwrap();
+
+        // 写入一半较大的内容后注入错误，模拟进程被杀或磁盘出错
+        let payload = "x".repeat(8 * 1024 * 1024);
+        let result = write_atomic_with(&notes_file, |file| {
+            file.write_all(&payload.as_bytes()[..payload.len() / 2])?;
+            Err(std::io::Error::other("injected failure"))
+        });
+
+        assert!(result.is_err());
+        let contents = fs::read_to_string(&notes_file).unwrap();
+        assert_eq!(contents, original);
+        assert!(notes::from_envelope(&contents).is_ok());
+        assert!(!tmp_path_for(&notes_file).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn truncated_notes_file_is_quarantined() {
+        let dir = temp_dir("recovery");
+        let notes_file = dir.join("notes.json");
+        let envelope =
+            notes::to_envelope(r#"[{"id":"a","title":"lost","body":"half"}]"#, false).unwrap();
+        let truncated = &envelope[..envelope.len() / 2];
+        fs::write(&notes_file, truncated).unwrap();
+
+        // 与 load_notes_with_recovery 走同一段恢复逻辑；截断的内容同样无法按 coerce_object 修正
+        let data = fs::read(&notes_file).unwrap();
+        let result = recover_notes_file(&notes_file, data, None, notes::from_envelope).unwrap();
+
+        assert!(result.recovered);
+        assert_eq!(result.notes, "[]");
+        assert!(!notes_file.exists());
+        let corrupt_file = PathBuf::from(result.corrupt_file.unwrap());
+        assert!(corrupt_file
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("notes.json.corrupt-"));
+        assert_eq!(fs::read_to_string(&corrupt_file).unwrap(), truncated);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn readable_or_newer_notes_file_is_not_quarantined() {
+        let dir = temp_dir("recovery-kept");
+        let notes_file = dir.join("notes.json");
+        let envelope = notes::to_envelope(r#"[{"id":"a","title":"t","body":"b"}]"#, false).unwrap();
+        fs::write(&notes_file, &envelope).unwrap();
+
+        let data = fs::read(&notes_file).unwrap();
+        let result = recover_notes_file(&notes_file, data, None, notes::from_envelope).unwrap();
+        assert!(!result.recovered);
+        assert!(result.corrupt_file.is_none());
+        assert_eq!(result.notes, notes::from_envelope(&envelope).unwrap());
+
+        // 版本比当前新的文件无法读取，但不能当作损坏文件移走
+        let newer = format!(r#"{{"schema_version": {}, "notes": []}}"#, notes::SCHEMA_VERSION + 1);
+        fs::write(&notes_file, &newer).unwrap();
+        let data = fs::read(&notes_file).unwrap();
+        let result = recover_notes_file(&notes_file, data, None, notes::from_envelope);
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&notes_file).unwrap(), newer);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
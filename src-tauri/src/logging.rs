@@ -0,0 +1,123 @@
+use std::cell::Cell;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::error::AppError;
+
+// 日志按天滚动写入 app_data_dir/logs/sticky.<日期>.log，最多保留 MAX_LOG_FILES 个文件
+pub const MAX_LOG_FILES: usize = 7;
+const LOG_PREFIX: &str = "sticky";
+const LOG_SUFFIX: &str = "log";
+
+// 后台写日志线程的句柄，需在应用运行期间一直持有，否则缓冲中的日志会丢失
+pub struct LogGuard(#[allow(dead_code)] WorkerGuard);
+
+pub fn log_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    Ok(crate::app_data_dir(app_handle)?.join("logs"))
+}
+
+// 同时输出到日志文件与标准错误
+pub fn init(app_handle: &tauri::AppHandle) -> Result<LogGuard, AppError> {
+    let dir = log_dir(app_handle)?;
+    fs::create_dir_all(&dir).map_err(AppError::io("Failed to create log directory"))?;
+    let appender = Builder::new()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix(LOG_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .map_err(AppError::platform("Failed to create log file"))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(false))
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .try_init()
+        .map_err(AppError::platform("Failed to initialize logging"))?;
+    Ok(LogGuard(guard))
+}
+
+// 最新的日志文件；尚未写入任何日志时返回日志目录
+pub fn latest_log_file(app_handle: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    let dir = log_dir(app_handle)?;
+    let latest = fs::read_dir(&dir)
+        .map_err(AppError::io("Failed to list log files"))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_PREFIX) && name.ends_with(LOG_SUFFIX))
+        })
+        // 文件名中的日期为 YYYY-MM-DD，按名称排序即按时间排序
+        .max();
+    Ok(latest.unwrap_or(dir))
+}
+
+thread_local! {
+    static COMMAND_FAILED: Cell<bool> = const { Cell::new(false) };
+}
+
+// AppError 序列化（即作为命令结果返回给前端）时调用
+pub fn mark_failed() {
+    COMMAND_FAILED.with(|failed| failed.set(true));
+}
+
+// 开启 timing_enabled 后每个命令分发完成时发出，供前端性能浮层显示
+pub const TIMING_EVENT: &str = "command-timing";
+
+#[derive(Serialize, Clone)]
+pub struct CommandTiming {
+    pub name: String,
+    pub millis: u64,
+}
+
+// 配置 timing_enabled 的内存副本，避免每次分发命令都读取配置文件；启动时与修改配置时更新
+static TIMING_ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_timing_enabled(enabled: bool) {
+    TIMING_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+// 包装命令分发，记录命令名、成败与耗时，开启 timing_enabled 时另发出 command-timing。
+// 同步命令在分发调用内执行并返回结果，因此可以通过本线程的标记判断是否返回了错误；
+// 异步命令在分发后即返回，耗时只包含分发本身
+pub fn with_command_log<R: tauri::Runtime>(
+    handler: impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let command = invoke.message.command().to_string();
+        let webview = TIMING_ENABLED
+            .load(Ordering::Relaxed)
+            .then(|| invoke.message.webview());
+        COMMAND_FAILED.with(|failed| failed.set(false));
+        let started = Instant::now();
+        let handled = handler(invoke);
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        if COMMAND_FAILED.with(Cell::get) {
+            tracing::warn!(command = %command, elapsed_ms, "command failed");
+        } else {
+            tracing::info!(command = %command, elapsed_ms, "command succeeded");
+        }
+        if let Some(webview) = webview {
+            let timing = CommandTiming {
+                name: command,
+                millis: elapsed_ms,
+            };
+            if let Err(e) = webview.emit(TIMING_EVENT, timing) {
+                tracing::warn!("Failed to emit command-timing: {}", e);
+            }
+        }
+        handled
+    }
+}
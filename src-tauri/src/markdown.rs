@@ -0,0 +1,128 @@
+use pulldown_cmark::{html, Options, Parser};
+
+use crate::notes::Note;
+
+// 把笔记正文渲染为 HTML 预览，并用 ammonia 移除脚本、事件属性
+// 与 javascript: 链接等危险内容
+pub fn render_html(body: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut rendered = String::with_capacity(body.len() * 3 / 2);
+    html::push_html(&mut rendered, Parser::new_ext(body, options));
+    ammonia::clean(&rendered)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 单条笔记导出为独立的 HTML 文件：样式内联、不引用任何外部资源，背景使用笔记颜色
+pub fn standalone_html(note: &Note) -> String {
+    let color = note
+        .color
+        .as_deref()
+        .filter(|color| crate::notes::is_hex_color(color))
+        .unwrap_or(crate::notes::DEFAULT_COLOR);
+    let title = note.title.trim();
+    let title = if title.is_empty() { "Note" } else { title };
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{title}</title>
+<style>
+body {{ margin: 0; padding: 2rem; background: #f0f0f0; font-family: system-ui, sans-serif; }}
+article {{ max-width: 48rem; margin: 0 auto; padding: 1.5rem 2rem; background: {color};
+  border-radius: 4px; box-shadow: 0 2px 8px rgba(0, 0, 0, 0.15); line-height: 1.6; }}
+h1 {{ margin-top: 0; font-size: 1.5rem; }}
+pre, code {{ background: rgba(0, 0, 0, 0.06); border-radius: 3px; }}
+pre {{ padding: 0.75rem; overflow-x: auto; }}
+img {{ max-width: 100%; }}
+</style>
+</head>
+<body>
+<article>
+<h1>{title}</h1>
+{body}
+</article>
+</body>
+</html>
+",
+        title = escape_html(title),
+        color = color,
+        body = render_html(&note.body),
+    )
+}
+
+// 每条笔记导出为一个二级标题小节，小节之间以 --- 分隔；无标题的笔记使用 "Note N"
+pub fn export(notes: &[Note]) -> String {
+    let sections: Vec<String> = notes
+        .iter()
+        .enumerate()
+        .map(|(index, note)| {
+            let title = note.title.trim();
+            let heading = if title.is_empty() {
+                format!("Note {}", index + 1)
+            } else {
+                // 标题必须保持在一行内
+                title.lines().collect::<Vec<_>>().join(" ")
+            };
+            let body = note.body.trim();
+            if body.is_empty() {
+                format!("## {}\n", heading)
+            } else {
+                format!("## {}\n\n{}\n", heading, body)
+            }
+        })
+        .collect();
+
+    sections.join("\n---\n\n")
+}
+
+// 按一、二级标题或 --- 分隔线拆分为多条笔记；标题作为笔记标题，其余内容作为正文
+pub fn import(source: &str) -> Vec<Note> {
+    let mut notes = Vec::new();
+    let mut title = String::new();
+    let mut body: Vec<&str> = Vec::new();
+    let mut in_code_block = false;
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+        // 代码块中的 # 与 --- 不作为分隔
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+
+        if !in_code_block {
+            let heading = trimmed
+                .strip_prefix("# ")
+                .or_else(|| trimmed.strip_prefix("## "));
+            if let Some(heading) = heading {
+                push_note(&mut notes, &mut title, &mut body);
+                title = heading.trim().to_string();
+                continue;
+            }
+            if trimmed == "---" {
+                push_note(&mut notes, &mut title, &mut body);
+                continue;
+            }
+        }
+
+        body.push(line);
+    }
+    push_note(&mut notes, &mut title, &mut body);
+
+    notes
+}
+
+fn push_note(notes: &mut Vec<Note>, title: &mut String, body: &mut Vec<&str>) {
+    let text = body.join("\n").trim().to_string();
+    if !title.is_empty() || !text.is_empty() {
+        notes.push(Note::new(std::mem::take(title), text));
+    }
+    body.clear();
+}
@@ -0,0 +1,147 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::error::AppError;
+use crate::notes::Note;
+
+// 每条笔记保存为 notes_md/<id>.md：开头的 YAML front matter 保存 id、时间、颜色等字段，
+// 其后为 Markdown 正文，可直接用外部 Markdown 编辑器修改
+pub const NOTES_MD_DIR: &str = "notes_md";
+
+const FENCE: &str = "---";
+
+// 笔记在数组中的位置，只写在 front matter 中，读取时用于排序后移除
+const POSITION_KEY: &str = "position";
+
+fn notes_dir(app_dir: &Path) -> PathBuf {
+    app_dir.join(NOTES_MD_DIR)
+}
+
+// id 直接用作文件名（外部新建的文件以文件名作为 id），不能包含路径分隔符等文件名中不允许的字符
+fn note_path(dir: &Path, id: &str) -> Result<PathBuf, AppError> {
+    let reserved = |c: char| c.is_control() || "/\\:*?\"<>|".contains(c);
+    let valid = !id.is_empty() && !id.starts_with('.') && !id.chars().any(reserved);
+    if !valid {
+        return Err(AppError::InvalidInput(format!(
+            "Note id cannot be used as a file name: {}",
+            id
+        )));
+    }
+    Ok(dir.join(format!("{}.md", id)))
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|extension| extension == "md")
+}
+
+fn to_markdown(note: &Note, position: usize) -> Result<String, AppError> {
+    let mut fields = match serde_json::to_value(note)
+        .map_err(AppError::serialization("Failed to serialize notes"))?
+    {
+        Value::Object(fields) => fields,
+        _ => Map::new(),
+    };
+    fields.remove("body");
+    fields.insert(POSITION_KEY.into(), position.into());
+    let front_matter = serde_yaml::to_string(&fields)
+        .map_err(AppError::serialization("Failed to serialize notes"))?;
+    Ok(format!("{}\n{}{}\n{}", FENCE, front_matter, FENCE, note.body))
+}
+
+// 拆出 front matter 与正文；文件不以 --- 行开头或没有结束行时视为没有 front matter
+fn split_front_matter(contents: &str) -> Option<(&str, &str)> {
+    let rest = contents
+        .strip_prefix("---\n")
+        .or_else(|| contents.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == FENCE {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+// 解析单个文件，返回 (位置, 笔记)；没有 id 的文件（如外部新建）以文件名作为 id 和默认标题
+fn parse(stem: &str, contents: &str) -> Result<(Option<u64>, Note), AppError> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        AppError::Serialization(format!("Failed to parse {}.md: {}", stem, e))
+    };
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    let (mut fields, body) = match split_front_matter(contents) {
+        Some((front_matter, body)) if front_matter.trim().is_empty() => (Map::new(), body),
+        Some((front_matter, body)) => {
+            let fields: Map<String, Value> =
+                serde_yaml::from_str(front_matter).map_err(|e| invalid(&e))?;
+            (fields, body)
+        }
+        None => (Map::new(), contents),
+    };
+
+    let position = fields.remove(POSITION_KEY).and_then(|position| position.as_u64());
+    if fields.get("id").is_none_or(Value::is_null) {
+        fields.insert("id".into(), stem.into());
+        fields.entry("title").or_insert_with(|| stem.into());
+    }
+    fields.insert("body".into(), body.into());
+    let note = serde_json::from_value(Value::Object(fields)).map_err(|e| invalid(&e))?;
+    Ok((position, note))
+}
+
+// 读取目录中的全部 .md 文件，按 position 排序，没有 position 的文件按文件名排在最后；
+// 无法解析的文件直接报错，避免随后保存时被当作已删除的笔记清理掉
+pub fn load_notes(app_dir: &Path) -> Result<Vec<Note>, AppError> {
+    let dir = notes_dir(app_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(AppError::io("Failed to read notes directory"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_markdown(path))
+        .collect();
+    paths.sort();
+
+    let mut notes = Vec::with_capacity(paths.len());
+    for path in paths {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let contents = fs::read_to_string(&path).map_err(AppError::io("Failed to load notes"))?;
+        notes.push(parse(&stem, &contents)?);
+    }
+    notes.sort_by_key(|(position, _)| position.unwrap_or(u64::MAX));
+    Ok(notes.into_iter().map(|(_, note)| note).collect())
+}
+
+// 以数组为准同步目录：只重写内容有变化的文件，全部写完后删除已不存在的笔记文件
+pub fn save_notes(app_dir: &Path, notes: Vec<Note>) -> Result<(), AppError> {
+    let dir = notes_dir(app_dir);
+    fs::create_dir_all(&dir).map_err(AppError::io("Failed to create notes directory"))?;
+
+    let mut written = HashSet::with_capacity(notes.len());
+    for (position, note) in notes.into_iter().map(crate::note_files::with_id).enumerate() {
+        let path = note_path(&dir, &note.id)?;
+        let contents = to_markdown(&note, position)?;
+        if !fs::read_to_string(&path).is_ok_and(|existing| existing == contents) {
+            crate::write_atomic(&path, contents.as_bytes())
+                .map_err(AppError::io("Failed to save notes"))?;
+        }
+        written.insert(path);
+    }
+
+    let entries = fs::read_dir(&dir).map_err(AppError::io("Failed to read notes directory"))?;
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if is_markdown(&path) && !written.contains(&path) {
+            if let Err(e) = fs::remove_file(&path) {
+                tracing::warn!("Failed to remove deleted note {}: {}", path.display(), e);
+            }
+        }
+    }
+    Ok(())
+}
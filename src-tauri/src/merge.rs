@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::notes::Note;
+
+// 保存时与磁盘上（可能已被其它设备同步修改）的笔记合并的结果，均为笔记 id
+#[derive(Serialize, Default)]
+pub struct MergeSummary {
+    // 磁盘上的版本更新，已替换本地版本
+    pub updated: Vec<String>,
+    // 其它设备新增的笔记，或本地删除但其它设备随后又修改过的笔记
+    pub added: Vec<String>,
+    // 其它设备删除、本地未再修改的笔记
+    pub removed: Vec<String>,
+    // 修订号过期、未写入的保存（见 save_notes）
+    pub stale: bool,
+}
+
+fn updated_at(note: &Note) -> Option<chrono::DateTime<chrono::Utc>> {
+    note.updated_at.as_deref().and_then(crate::timestamp::parse)
+}
+
+// 自 base 以来是否修改过
+fn modified_since(note: &Note, base: &Note) -> bool {
+    updated_at(note) > updated_at(base)
+}
+
+// 以前端上次加载的 base 为共同祖先，逐条合并本地（local）与磁盘（remote）的笔记：
+// 两边都有的保留修改时间较新的一方（相同时保留本地）；一方删除而另一方自 base 以来未修改的，删除生效，
+// 否则保留修改过的一方。结果保持本地顺序，磁盘独有的笔记追加在末尾
+pub fn three_way(base: &[Note], local: Vec<Note>, remote: Vec<Note>) -> (Vec<Note>, MergeSummary) {
+    let base: HashMap<&str, &Note> = base.iter().map(|note| (note.id.as_str(), note)).collect();
+    let remote_index: HashMap<String, usize> = remote
+        .iter()
+        .enumerate()
+        .map(|(index, note)| (note.id.clone(), index))
+        .collect();
+    // 已与本地合并的磁盘笔记取出，剩下的即为磁盘独有
+    let mut remote: Vec<Option<Note>> = remote.into_iter().map(Some).collect();
+
+    let mut summary = MergeSummary::default();
+    let mut merged = Vec::with_capacity(local.len());
+    for note in local {
+        let theirs = remote_index
+            .get(&note.id)
+            .and_then(|&index| remote[index].take());
+        match theirs {
+            Some(theirs) if updated_at(&theirs) > updated_at(&note) => {
+                summary.updated.push(theirs.id.clone());
+                merged.push(theirs);
+            }
+            Some(_) => merged.push(note),
+            None => match base.get(note.id.as_str()) {
+                Some(base_note) if !modified_since(&note, base_note) => {
+                    summary.removed.push(note.id.clone());
+                }
+                _ => merged.push(note),
+            },
+        }
+    }
+
+    for theirs in remote.into_iter().flatten() {
+        let keep = match base.get(theirs.id.as_str()) {
+            Some(base_note) => modified_since(&theirs, base_note),
+            None => true,
+        };
+        if keep {
+            summary.added.push(theirs.id.clone());
+            merged.push(theirs);
+        }
+    }
+    (merged, summary)
+}
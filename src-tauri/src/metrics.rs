@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+// 本地使用统计，保存在 app_data_dir/metrics.json，只在开启 metrics_enabled 后记录，从不发送到网络
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Metrics {
+    pub launches: u64,
+    pub notes_created: u64,
+    pub tasks_completed: u64,
+    pub saves: u64,
+}
+
+#[derive(Clone, Copy)]
+pub enum Counter {
+    Launches,
+    NotesCreated,
+    TasksCompleted,
+    Saves,
+}
+
+// 串行化读-改-写，避免并发保存时丢失计数
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn metrics_file(app_handle: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    Ok(crate::app_data_dir(app_handle)?.join("metrics.json"))
+}
+
+// 文件缺失或无法解析时返回全零的统计
+pub fn load(app_handle: &tauri::AppHandle) -> Metrics {
+    metrics_file(app_handle)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save(app_handle: &tauri::AppHandle, metrics: &Metrics) -> Result<(), AppError> {
+    let app_dir = crate::app_data_dir(app_handle)?;
+    fs::create_dir_all(&app_dir).map_err(AppError::io("Failed to create app directory"))?;
+
+    let contents = serde_json::to_string(metrics)
+        .map_err(AppError::serialization("Failed to serialize metrics"))?;
+    crate::write_atomic(&metrics_file(app_handle)?, contents.as_bytes())
+        .map_err(AppError::io("Failed to save metrics"))
+}
+
+pub fn increment(app_handle: &tauri::AppHandle, counter: Counter) {
+    add(app_handle, counter, 1);
+}
+
+// 未开启统计时不做任何事；记录失败只写日志，不影响正在进行的操作
+pub fn add(app_handle: &tauri::AppHandle, counter: Counter, amount: u64) {
+    if amount == 0 || !crate::config::load(app_handle).metrics_enabled {
+        return;
+    }
+    let _guard = LOCK.lock().unwrap();
+    let mut metrics = load(app_handle);
+    let value = match counter {
+        Counter::Launches => &mut metrics.launches,
+        Counter::NotesCreated => &mut metrics.notes_created,
+        Counter::TasksCompleted => &mut metrics.tasks_completed,
+        Counter::Saves => &mut metrics.saves,
+    };
+    *value += amount;
+    if let Err(e) = save(app_handle, &metrics) {
+        tracing::warn!("Failed to update metrics: {}", e);
+    }
+}
+
+pub fn reset(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let _guard = LOCK.lock().unwrap();
+    save(app_handle, &Metrics::default())
+}
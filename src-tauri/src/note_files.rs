@@ -0,0 +1,149 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::notes::Note;
+
+// 每条笔记单独保存为 notes/<id>.json，notes/index.json 记录顺序与列表展示所需的元数据，
+// 修改单条笔记只需重写对应文件（标题或置顶状态变化时再更新索引）
+pub const NOTES_DIR: &str = "notes";
+const INDEX_FILE: &str = "index.json";
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct IndexEntry {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    pinned: bool,
+}
+
+impl IndexEntry {
+    fn for_note(note: &Note) -> Self {
+        IndexEntry {
+            id: note.id.clone(),
+            title: note.title.clone(),
+            pinned: note.pinned,
+        }
+    }
+}
+
+fn notes_dir(app_dir: &Path) -> PathBuf {
+    app_dir.join(NOTES_DIR)
+}
+
+// id 直接用作文件名，只允许字母、数字、- 与 _
+fn note_path(dir: &Path, id: &str) -> Result<PathBuf, AppError> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(AppError::InvalidInput(format!(
+            "Note id cannot be used as a file name: {}",
+            id
+        )));
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+fn load_index(dir: &Path) -> Result<Vec<IndexEntry>, AppError> {
+    let index_file = dir.join(INDEX_FILE);
+    if !index_file.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(index_file).map_err(AppError::io("Failed to load note index"))?;
+    serde_json::from_str(&contents).map_err(AppError::serialization("Failed to parse note index"))
+}
+
+fn save_index(dir: &Path, index: &[IndexEntry]) -> Result<(), AppError> {
+    let contents = serde_json::to_string(index)
+        .map_err(AppError::serialization("Failed to serialize note index"))?;
+    crate::write_atomic(&dir.join(INDEX_FILE), contents.as_bytes())
+        .map_err(AppError::io("Failed to save note index"))
+}
+
+// 内容未变化时跳过写入
+fn write_note(dir: &Path, note: &Note) -> Result<(), AppError> {
+    let path = note_path(dir, &note.id)?;
+    let contents =
+        serde_json::to_vec(note).map_err(AppError::serialization("Failed to serialize notes"))?;
+    if fs::read(&path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    crate::write_atomic(&path, &contents).map_err(AppError::io("Failed to save notes"))
+}
+
+// 缺失 id 的笔记分配新 id
+pub fn with_id(mut note: Note) -> Note {
+    if note.id.is_empty() {
+        note.id = uuid::Uuid::new_v4().to_string();
+    }
+    note
+}
+
+// 按索引顺序读取；索引中存在但文件缺失的笔记跳过
+pub fn load_notes(app_dir: &Path) -> Result<Vec<Note>, AppError> {
+    let dir = notes_dir(app_dir);
+    let mut notes = Vec::new();
+    for entry in load_index(&dir)? {
+        let path = note_path(&dir, &entry.id)?;
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                tracing::warn!("Skipping note {}: {}", entry.id, e);
+                continue;
+            }
+        };
+        let note = serde_json::from_str(&contents)
+            .map_err(AppError::serialization("Failed to parse notes"))?;
+        notes.push(note);
+    }
+    Ok(notes)
+}
+
+// 以数组为准整体保存：只重写内容有变化的笔记文件，并删除已不存在的笔记文件
+pub fn save_notes(app_dir: &Path, notes: Vec<Note>) -> Result<(), AppError> {
+    let dir = notes_dir(app_dir);
+    fs::create_dir_all(&dir).map_err(AppError::io("Failed to create notes directory"))?;
+
+    let notes: Vec<Note> = notes.into_iter().map(with_id).collect();
+    for note in &notes {
+        write_note(&dir, note)?;
+    }
+    let index: Vec<IndexEntry> = notes.iter().map(IndexEntry::for_note).collect();
+    let previous = load_index(&dir).unwrap_or_default();
+    if previous != index {
+        save_index(&dir, &index)?;
+    }
+
+    // 索引写入后再删除文件，中途失败时最多残留多余文件
+    for entry in previous {
+        if !index.iter().any(|current| current.id == entry.id) {
+            let _ = fs::remove_file(note_path(&dir, &entry.id)?);
+        }
+    }
+    Ok(())
+}
+
+// 保存单条笔记（新增或覆盖），返回笔记 id
+pub fn save_note(app_dir: &Path, note: Note) -> Result<String, AppError> {
+    let dir = notes_dir(app_dir);
+    fs::create_dir_all(&dir).map_err(AppError::io("Failed to create notes directory"))?;
+
+    let note = with_id(note);
+    write_note(&dir, &note)?;
+
+    let mut index = load_index(&dir)?;
+    let entry = IndexEntry::for_note(&note);
+    match index.iter_mut().find(|current| current.id == note.id) {
+        Some(current) if *current == entry => return Ok(note.id),
+        Some(current) => *current = entry,
+        None => index.push(entry),
+    }
+    save_index(&dir, &index)?;
+    Ok(note.id)
+}
@@ -0,0 +1,103 @@
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::error::AppError;
+
+// 单条笔记弹出窗口的标签前缀，标签为前缀加笔记 id
+pub const LABEL_PREFIX: &str = "note-";
+// 弹出窗口的逻辑尺寸
+const WIDTH: f64 = 240.0;
+const HEIGHT: f64 = 240.0;
+// 新窗口依次错开的距离，以及与主窗口的间隔
+const CASCADE_OFFSET: f64 = 24.0;
+const MARGIN: f64 = 16.0;
+
+// 窗口标签只允许字母、数字与 -、_ 等字符，其余字符替换为 _
+pub fn label_for(id: &str) -> String {
+    let id: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}{}", LABEL_PREFIX, id)
+}
+
+pub fn is_note_window(label: &str) -> bool {
+    label.starts_with(LABEL_PREFIX)
+}
+
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+                char::from(byte).to_string()
+            } else {
+                format!("%{:02X}", byte)
+            }
+        })
+        .collect()
+}
+
+// 新窗口放在主窗口右侧，已打开多个时依次错开
+fn initial_position(app_handle: &tauri::AppHandle, open_count: usize) -> Option<(f64, f64)> {
+    let main = app_handle.get_webview_window("main")?;
+    let scale = main.scale_factor().ok()?;
+    let position = main.outer_position().ok()?.to_logical::<f64>(scale);
+    let size = main.outer_size().ok()?.to_logical::<f64>(scale);
+    let offset = CASCADE_OFFSET * open_count as f64;
+    Some((position.x + size.width + MARGIN + offset, position.y + offset))
+}
+
+// 打开笔记的独立窗口（页面为 index.html?note=<id>）；该笔记已有窗口时改为聚焦该窗口
+pub fn open(app_handle: &tauri::AppHandle, id: &str) -> Result<(), AppError> {
+    let label = label_for(id);
+    if let Some(window) = app_handle.get_webview_window(&label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        window.set_focus()?;
+        return Ok(());
+    }
+
+    let open_count = app_handle
+        .webview_windows()
+        .keys()
+        .filter(|label| is_note_window(label))
+        .count();
+    let url = WebviewUrl::App(format!("index.html?note={}", encode_query(id)).into());
+    let mut builder = WebviewWindowBuilder::new(app_handle, &label, url)
+        .title("便签")
+        .inner_size(WIDTH, HEIGHT)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true);
+    if let Some((x, y)) = initial_position(app_handle, open_count) {
+        builder = builder.position(x, y);
+    }
+    builder.build()?;
+
+    let mut config = crate::config::load(app_handle);
+    if !config.note_windows.iter().any(|open| open == id) {
+        config.note_windows.push(id.to_string());
+        crate::config::save(app_handle, &config)?;
+    }
+    Ok(())
+}
+
+// 用户关闭弹出窗口时调用；退出应用时窗口直接销毁，不经过这里，下次启动仍会恢复
+pub fn forget(app_handle: &tauri::AppHandle, label: &str) -> Result<(), AppError> {
+    let mut config = crate::config::load(app_handle);
+    let before = config.note_windows.len();
+    config.note_windows.retain(|id| label_for(id) != label);
+    if config.note_windows.len() == before {
+        return Ok(());
+    }
+    crate::config::save(app_handle, &config)
+}
+
+// 启动时重新打开上次退出时仍弹出的笔记窗口
+pub fn restore(app_handle: &tauri::AppHandle) {
+    for id in crate::config::load(app_handle).note_windows {
+        if let Err(e) = open(app_handle, &id) {
+            tracing::warn!("Failed to restore note window {}: {}", id, e);
+        }
+    }
+}
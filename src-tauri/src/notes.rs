@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::AppError;
+
+// notes.json 的当前结构版本：{ "schema_version": 2, "notes": [...] }。
+// 版本 1 为早期直接保存的笔记数组
+pub const SCHEMA_VERSION: u64 = 2;
+
+// 未设置颜色的笔记使用的默认颜色（便签黄）
+pub const DEFAULT_COLOR: &str = "#fff59d";
+
+// 笔记的已知字段；其余字段原样保存在 extra 中，避免后端改写时丢失前端数据
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Note {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub id: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub title: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    // 十六进制颜色 #rrggbb
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    // 置顶笔记在 load_notes_sorted 中排在最前
+    #[serde(default, deserialize_with = "null_as_default", skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    // 标签（如 work、personal），匹配时忽略大小写与开头的 #
+    #[serde(default, deserialize_with = "null_as_default", skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // 提醒时间（ISO-8601），到时由 reminders 后台扫描发出系统通知
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remind_at: Option<String>,
+    // 为 true 时正文在存储中单独加密，见 secure_notes
+    #[serde(default, deserialize_with = "null_as_default", skip_serializing_if = "std::ops::Not::not")]
+    pub secure: bool,
+    // 正文被修改的次数，每次写入时由后端按存储中的上一版本计算，已有笔记前端传入的值会被忽略
+    #[serde(default, deserialize_with = "null_as_default", skip_serializing_if = "is_zero")]
+    pub revision_count: u64,
+    // 附带图片相对于数据目录的路径，见 attachments
+    #[serde(default, deserialize_with = "null_as_default", skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Note {
+    // 新建笔记：分配新 id，创建与修改时间取当前时间
+    pub fn new(title: String, body: String) -> Self {
+        let now = crate::timestamp::now_iso();
+        Note {
+            id: uuid::Uuid::new_v4().to_string(),
+            title,
+            body,
+            created_at: Some(now.clone()),
+            updated_at: Some(now),
+            color: Some(DEFAULT_COLOR.to_string()),
+            pinned: false,
+            tags: Vec::new(),
+            remind_at: None,
+            secure: false,
+            revision_count: 0,
+            attachments: Vec::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
+// 新建笔记，id 与已有笔记重复时重新生成
+pub fn new_unique(existing: &[Note], title: String, body: String) -> Note {
+    let mut note = Note::new(title, body);
+    if existing.iter().any(|other| other.id == note.id) {
+        note.id = unique_id(existing);
+    }
+    note
+}
+
+// 生成与已有笔记都不重复的 id
+pub fn unique_id(existing: &[Note]) -> String {
+    loop {
+        let id = uuid::Uuid::new_v4().to_string();
+        if !existing.iter().any(|other| other.id == id) {
+            return id;
+        }
+    }
+}
+
+// 记录修改时间
+pub fn touch(note: &mut Note) {
+    note.updated_at = Some(crate::timestamp::now_iso());
+}
+
+// 只读查找，用于导出、统计等不修改笔记的命令
+pub fn find<'a>(notes: &'a [Note], id: &str) -> Result<&'a Note, AppError> {
+    notes
+        .iter()
+        .find(|note| note.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Note not found: {}", id)))
+}
+
+pub fn find_mut<'a>(notes: &'a mut [Note], id: &str) -> Result<&'a mut Note, AppError> {
+    notes
+        .iter_mut()
+        .find(|note| note.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Note not found: {}", id)))
+}
+
+// 把笔记移到数组中的 new_index 处，超出范围的位置按首尾处理
+pub fn reorder(notes: &mut Vec<Note>, id: &str, new_index: usize) -> Result<(), AppError> {
+    let index = notes
+        .iter()
+        .position(|note| note.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Note not found: {}", id)))?;
+    let note = notes.remove(index);
+    notes.insert(new_index.min(notes.len()), note);
+    Ok(())
+}
+
+// 标题与正文都为空白，且没有标签、颜色为默认值（或未设置）的笔记；
+// 设置了标签或其它颜色的空笔记可能是有意保留的占位，不算空笔记
+pub fn is_blank(note: &Note) -> bool {
+    note.title.trim().is_empty()
+        && note.body.trim().is_empty()
+        && note.tags.is_empty()
+        && note.color.as_deref().is_none_or(|color| color.eq_ignore_ascii_case(DEFAULT_COLOR))
+}
+
+// 置顶笔记在前，其余按修改时间从新到旧；没有修改时间的排在最后
+pub fn sort_pinned_first(notes: &mut [Note]) {
+    notes.sort_by_key(|note| {
+        let updated_at = note.updated_at.as_deref().and_then(crate::timestamp::parse);
+        (std::cmp::Reverse(note.pinned), std::cmp::Reverse(updated_at))
+    });
+}
+
+// get_recent_notes 单次最多返回的笔记数
+pub const MAX_RECENT: usize = 100;
+
+// 按修改时间从新到旧取前 limit 条；没有修改时间的按创建时间，两者都没有的排在最后
+pub fn recent(mut notes: Vec<Note>, limit: usize) -> Vec<Note> {
+    notes.sort_by_key(|note| {
+        let changed_at = note
+            .updated_at
+            .as_deref()
+            .and_then(crate::timestamp::parse)
+            .or_else(|| note.created_at.as_deref().and_then(crate::timestamp::parse));
+        std::cmp::Reverse(changed_at)
+    });
+    notes.truncate(limit.min(MAX_RECENT));
+    notes
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+// 按 id 与存储中的上一版本比较：正文有变化时修改次数加一，否则沿用原来的次数；
+// 上一版本中没有的笔记（新建、从回收站恢复或导入）保留自身的次数，新建的笔记为 0
+pub fn count_revisions(previous: &[Note], notes: &mut [Note]) {
+    let previous: HashMap<&str, &Note> =
+        previous.iter().map(|note| (note.id.as_str(), note)).collect();
+    for note in notes.iter_mut() {
+        note.revision_count = match previous.get(note.id.as_str()) {
+            Some(prior) if prior.body != note.body => prior.revision_count + 1,
+            Some(prior) => prior.revision_count,
+            None => note.revision_count,
+        };
+    }
+}
+
+// 按修改次数从多到少取前 limit 条（最多 MAX_RECENT 条），次数相同的保持原有顺序
+pub fn most_edited(mut notes: Vec<Note>, limit: usize) -> Vec<Note> {
+    notes.sort_by_key(|note| std::cmp::Reverse(note.revision_count));
+    notes.truncate(limit.min(MAX_RECENT));
+    notes
+}
+
+pub fn is_hex_color(color: &str) -> bool {
+    color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// 字段缺失或为 null 时都使用默认值
+pub fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+pub fn parse(notes_json: &str) -> Result<Vec<Note>, AppError> {
+    serde_json::from_str(notes_json).map_err(AppError::serialization("Failed to parse notes"))
+}
+
+pub fn to_json(notes: &[Note]) -> Result<String, AppError> {
+    serde_json::to_string(notes).map_err(AppError::serialization("Failed to serialize notes"))
+}
+
+// 把笔记数组包装为当前版本的文件内容；pretty 为 true 时输出带缩进的多行 JSON
+pub fn to_envelope(notes_json: &str, pretty: bool) -> Result<String, AppError> {
+    let notes: Value =
+        serde_json::from_str(notes_json).map_err(AppError::serialization("Failed to parse notes"))?;
+    if !notes.is_array() {
+        return Err(AppError::InvalidInput("Notes must be an array".to_string()));
+    }
+    let mut envelope = Map::new();
+    envelope.insert("schema_version".into(), SCHEMA_VERSION.into());
+    envelope.insert("notes".into(), notes);
+    let contents = if pretty {
+        serde_json::to_string_pretty(&envelope)
+    } else {
+        serde_json::to_string(&envelope)
+    };
+    contents.map_err(AppError::serialization("Failed to serialize notes"))
+}
+
+// 从文件内容中取出笔记数组，旧版本依次升级到当前版本；无法解析时返回 Serialization 错误
+pub fn from_envelope(contents: &str) -> Result<String, AppError> {
+    let value: Value =
+        serde_json::from_str(contents).map_err(AppError::serialization("Failed to parse notes"))?;
+    let (version, notes) = match value {
+        Value::Array(notes) => (1, notes),
+        Value::Object(mut envelope) => {
+            let version = envelope.get("schema_version").and_then(Value::as_u64);
+            match (version, envelope.remove("notes")) {
+                (Some(version), Some(Value::Array(notes))) => (version, notes),
+                _ => {
+                    return Err(AppError::Serialization(
+                        "Failed to parse notes: missing schema_version or notes".to_string(),
+                    ))
+                }
+            }
+        }
+        _ => {
+            return Err(AppError::Serialization(
+                "Failed to parse notes: unexpected file structure".to_string(),
+            ))
+        }
+    };
+    if version > SCHEMA_VERSION {
+        return Err(AppError::InvalidInput(format!(
+            "Notes were saved by a newer version of the app (schema version {})",
+            version
+        )));
+    }
+
+    let notes = migrate(version, notes);
+    serde_json::to_string(&notes).map_err(AppError::serialization("Failed to serialize notes"))
+}
+
+// coerce_object 对文件内容所做的修正
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Coercion {
+    // 取出了对象中的 notes 数组
+    Unwrapped,
+    // 无法识别的对象，已按空数组处理
+    Reset,
+}
+
+// 处理 from_envelope 无法识别的对象（早期有问题的版本曾写入 {}）：带 notes 数组的对象取出该数组
+// 并按其版本（缺失时为 1）升级，其余对象视为空数组。不是对象或来自更新版本时返回 None
+pub fn coerce_object(contents: &str) -> Option<(String, Coercion)> {
+    let Ok(Value::Object(mut object)) = serde_json::from_str::<Value>(contents) else {
+        return None;
+    };
+    let version = object.get("schema_version").and_then(Value::as_u64).unwrap_or(1);
+    if version > SCHEMA_VERSION {
+        return None;
+    }
+    let (notes, coercion) = match object.remove("notes") {
+        Some(Value::Array(notes)) => (migrate(version, notes), Coercion::Unwrapped),
+        _ => (Vec::new(), Coercion::Reset),
+    };
+    let notes = serde_json::to_string(&notes).ok()?;
+    Some((notes, coercion))
+}
+
+// 逐版本升级，每一步只负责从 version 升到 version + 1
+fn migrate(mut version: u64, mut notes: Vec<Value>) -> Vec<Value> {
+    while version < SCHEMA_VERSION {
+        if version == 1 {
+            notes.iter_mut().for_each(migrate_v1_note);
+        }
+        version += 1;
+    }
+    notes
+}
+
+// v1 -> v2：补齐 created_at（优先沿用 updated_at）与 color
+fn migrate_v1_note(note: &mut Value) {
+    let Some(note) = note.as_object_mut() else {
+        return;
+    };
+    if note.get("created_at").is_none_or(Value::is_null) {
+        let created_at = match note.get("updated_at") {
+            Some(Value::String(updated_at)) => updated_at.clone(),
+            _ => crate::timestamp::now_iso(),
+        };
+        note.insert("created_at".into(), created_at.into());
+    }
+    if note.get("color").is_none_or(Value::is_null) {
+        note.insert("color".into(), DEFAULT_COLOR.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_array_is_upgraded_to_current_version() {
+        let v1 = r##"[
+            {"id": "a", "title": "t", "body": "b", "updated_at": "2024-01-02T03:04:05.000Z"},
+            {"id": "b", "title": "t", "body": "b", "color": "#abcdef"}
+        ]"##;
+        let notes = parse(&from_envelope(v1).unwrap()).unwrap();
+
+        assert_eq!(notes.len(), 2);
+        // created_at 优先沿用 updated_at，缺少颜色时补默认颜色
+        assert_eq!(notes[0].created_at.as_deref(), Some("2024-01-02T03:04:05.000Z"));
+        assert_eq!(notes[0].color.as_deref(), Some(DEFAULT_COLOR));
+        assert!(notes[1].created_at.is_some());
+        assert_eq!(notes[1].color.as_deref(), Some("#abcdef"));
+    }
+
+    #[test]
+    fn v1_envelope_is_upgraded() {
+        let v1 = r#"{"schema_version": 1, "notes": [{"id": "a", "color": null}]}"#;
+        let notes = parse(&from_envelope(v1).unwrap()).unwrap();
+        assert!(notes[0].created_at.is_some());
+        assert_eq!(notes[0].color.as_deref(), Some(DEFAULT_COLOR));
+    }
+
+    #[test]
+    fn current_version_round_trips() {
+        let notes = r##"[{"id":"a","title":"t","body":"b","created_at":"x","color":"#000000"}]"##;
+        let envelope = to_envelope(notes, false).unwrap();
+        let restored: Value = serde_json::from_str(&from_envelope(&envelope).unwrap()).unwrap();
+        assert_eq!(restored, serde_json::from_str::<Value>(notes).unwrap());
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let envelope = format!(r#"{{"schema_version": {}, "notes": []}}"#, SCHEMA_VERSION + 1);
+        assert!(matches!(from_envelope(&envelope), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn empty_object_is_reset_to_empty_array() {
+        assert!(matches!(from_envelope("{}"), Err(AppError::Serialization(_))));
+        let (notes, coercion) = coerce_object("{}").unwrap();
+        assert_eq!(notes, "[]");
+        assert!(matches!(coercion, Coercion::Reset));
+    }
+
+    #[test]
+    fn object_with_notes_array_is_unwrapped() {
+        let contents = r#"{"notes": [{"id": "a", "title": "t", "body": "b"}]}"#;
+        assert!(matches!(from_envelope(contents), Err(AppError::Serialization(_))));
+        let (notes, coercion) = coerce_object(contents).unwrap();
+        assert!(matches!(coercion, Coercion::Unwrapped));
+
+        // 缺少 schema_version 时按版本 1 升级
+        let notes = parse(&notes).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].id, "a");
+        assert_eq!(notes[0].color.as_deref(), Some(DEFAULT_COLOR));
+        assert!(notes[0].created_at.is_some());
+    }
+
+    #[test]
+    fn coerce_object_ignores_arrays_and_newer_versions() {
+        assert!(coerce_object("[]").is_none());
+        assert!(coerce_object("not json").is_none());
+        let newer = format!(r#"{{"schema_version": {}, "notes": []}}"#, SCHEMA_VERSION + 1);
+        assert!(coerce_object(&newer).is_none());
+    }
+
+    #[test]
+    fn migrate_v1_note_keeps_existing_fields() {
+        let mut note = serde_json::json!({
+            "id": "a",
+            "created_at": "2023-01-01T00:00:00.000Z",
+            "updated_at": "2024-01-01T00:00:00.000Z",
+            "color": "#123456"
+        });
+        let expected = note.clone();
+        migrate_v1_note(&mut note);
+        assert_eq!(note, expected);
+
+        // 不是对象的条目原样保留
+        let mut value = Value::from("not a note");
+        migrate_v1_note(&mut value);
+        assert_eq!(value, Value::from("not a note"));
+    }
+}
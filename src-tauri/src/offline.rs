@@ -0,0 +1,146 @@
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::error::AppError;
+
+// 自定义存储目录（如网络共享）无法访问时发出，恢复访问并写入缓存的保存后发出 storage-online
+pub const OFFLINE_EVENT: &str = "storage-offline";
+pub const ONLINE_EVENT: &str = "storage-online";
+
+// 重试间隔从 RETRY_INITIAL 开始逐次翻倍，最长 RETRY_MAX
+const RETRY_INITIAL: Duration = Duration::from_secs(1);
+const RETRY_MAX: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Clone)]
+pub struct OfflineEvent {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct OnlineEvent {
+    pub path: String,
+}
+
+// 未能写入的保存，恢复后按原参数重新执行 save_notes_now
+pub struct PendingSave {
+    pub notes: String,
+    pub base: Option<String>,
+    pub expected_hash: Option<String>,
+}
+
+// 目录无法访问期间最近一次未能写入的保存，恢复后写入
+#[derive(Default)]
+pub struct StorageOffline {
+    pending: Mutex<Option<PendingSave>>,
+    offline: AtomicBool,
+    retrying: AtomicBool,
+}
+
+// 只检查配置中的自定义目录：STICKY_DATA_DIR 与降级用的临时目录都在本机，不会掉线
+fn configured_dir(app_handle: &tauri::AppHandle) -> Option<String> {
+    let overridden = crate::data_override::data_dir().is_some();
+    if overridden || crate::fallback::current(app_handle).is_some() {
+        return None;
+    }
+    crate::config::load(app_handle).storage_path
+}
+
+// 读取目录内容以确认可访问；storage_dir 会在自定义目录不存在时回退到 app_data_dir，
+// 保存与加载前必须先探测，否则共享断开时会读到或写入另一份数据
+pub fn probe(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let Some(path) = configured_dir(app_handle) else {
+        return Ok(());
+    };
+    match fs::read_dir(&path) {
+        Ok(_) => {
+            mark_online(app_handle, &path);
+            Ok(())
+        }
+        Err(e) => {
+            let reason = format!("Storage directory {} is unreachable: {}", path, e);
+            mark_offline(app_handle, &path, &reason);
+            Err(AppError::StorageUnavailable(reason))
+        }
+    }
+}
+
+// 保存因目录无法访问而失败后调用：只保留最新的一次保存，恢复后写入
+pub fn buffer(app_handle: &tauri::AppHandle, save: PendingSave) {
+    let state = app_handle.state::<StorageOffline>();
+    *state.pending.lock().unwrap() = Some(save);
+}
+
+// 目录可访问时直接保存的内容更新，丢弃缓存的旧保存，避免恢复后被覆盖
+pub fn discard(app_handle: &tauri::AppHandle) {
+    app_handle.state::<StorageOffline>().pending.lock().unwrap().take();
+}
+
+fn mark_offline(app_handle: &tauri::AppHandle, path: &str, reason: &str) {
+    let state = app_handle.state::<StorageOffline>();
+    if !state.offline.swap(true, Ordering::SeqCst) {
+        tracing::warn!("{}", reason);
+        let event = OfflineEvent {
+            path: path.to_string(),
+            reason: reason.to_string(),
+        };
+        if let Err(e) = app_handle.emit(OFFLINE_EVENT, event) {
+            tracing::warn!("Failed to emit storage-offline: {}", e);
+        }
+    }
+    start_retry(app_handle);
+}
+
+fn mark_online(app_handle: &tauri::AppHandle, path: &str) {
+    let state = app_handle.state::<StorageOffline>();
+    if state.offline.swap(false, Ordering::SeqCst) {
+        tracing::info!("Storage directory {} is reachable again", path);
+        let event = OnlineEvent {
+            path: path.to_string(),
+        };
+        if let Err(e) = app_handle.emit(ONLINE_EVENT, event) {
+            tracing::warn!("Failed to emit storage-online: {}", e);
+        }
+    }
+}
+
+// 后台按退避间隔探测，目录恢复后在保存队列中写入缓存的保存；同一时间只有一个重试线程
+fn start_retry(app_handle: &tauri::AppHandle) {
+    if app_handle.state::<StorageOffline>().retrying.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        let mut delay = RETRY_INITIAL;
+        loop {
+            std::thread::sleep(delay);
+            let path = configured_dir(&app_handle);
+            if path.is_none_or(|path| fs::read_dir(path).is_ok()) {
+                break;
+            }
+            delay = (delay * 2).min(RETRY_MAX);
+        }
+        let state = app_handle.state::<StorageOffline>();
+        state.retrying.store(false, Ordering::SeqCst);
+
+        let _guard = crate::save_queue::lock(&app_handle);
+        let pending = state.pending.lock().unwrap().take();
+        if let Some(PendingSave {
+            notes,
+            base,
+            expected_hash,
+        }) = pending
+        {
+            if let Err(e) = crate::save_notes_now(&app_handle, notes, base, expected_hash) {
+                tracing::warn!("Failed to save buffered notes: {}", e);
+            }
+        } else if let Err(e) = probe(&app_handle) {
+            tracing::warn!("{}", e);
+        }
+    });
+}
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use printpdf::{IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference};
+
+use crate::error::AppError;
+use crate::notes::Note;
+
+// A4 纵向，四周留白 20mm
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+
+const TITLE_SIZE: f32 = 18.0;
+const BODY_SIZE: f32 = 11.0;
+const LINE_SPACING: f32 = 1.5;
+const PT_TO_MM: f32 = 0.3528;
+
+// 未配置 pdf_font_path 时依次尝试的系统字体，需能显示中文；仅支持 TrueType（.ttf）字体
+const FONT_CANDIDATES: [&str; 7] = [
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\simkai.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/google-droid/DroidSansFallback.ttf",
+    "/usr/share/fonts/truetype/wqy/wqy-zenhei.ttf",
+];
+
+pub fn find_font(configured: Option<&str>) -> Result<PathBuf, AppError> {
+    if let Some(path) = configured {
+        let path = PathBuf::from(path);
+        if path.is_file() {
+            return Ok(path);
+        }
+        return Err(AppError::InvalidInput(format!(
+            "Configured PDF font does not exist: {}",
+            path.display()
+        )));
+    }
+    FONT_CANDIDATES
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            AppError::NotFound(
+                "No Unicode font found, set pdf_font_path in config.json".to_string(),
+            )
+        })
+}
+
+// 没有字体度量信息，按全角字符 1 个字号宽、半角字符半个字号宽估算
+fn char_width(c: char, font_size: f32) -> f32 {
+    let em = font_size * PT_TO_MM;
+    if c.is_ascii() {
+        em * 0.5
+    } else {
+        em
+    }
+}
+
+// 按可用宽度折行：英文尽量在空格处断开，中文等可在任意字符处断开
+fn wrap(line: &str, font_size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current: Vec<char> = Vec::new();
+    let mut width = 0.0;
+    for c in line.chars() {
+        let w = char_width(c, font_size);
+        if width + w > max_width && !current.is_empty() {
+            let split = current
+                .iter()
+                .rposition(|c| *c == ' ')
+                .filter(|index| *index > 0)
+                .unwrap_or(current.len());
+            let rest = current.split_off(split);
+            lines.push(current.iter().collect::<String>().trim_end().to_string());
+            current = rest.into_iter().skip_while(|c| *c == ' ').collect();
+            width = current.iter().map(|c| char_width(*c, font_size)).sum();
+        }
+        current.push(c);
+        width += w;
+    }
+    lines.push(current.into_iter().collect());
+    lines
+}
+
+// 逐行输出文本，超出页面底部时新建一页
+struct Writer<'a> {
+    doc: &'a PdfDocumentReference,
+    font: IndirectFontRef,
+    layer: PdfLayerReference,
+    y: f32,
+}
+
+impl Writer<'_> {
+    fn line(&mut self, text: &str, font_size: f32) {
+        let height = font_size * PT_TO_MM * LINE_SPACING;
+        if self.y - height < MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.y -= height;
+        if !text.is_empty() {
+            self.layer
+                .use_text(text, font_size, Mm(MARGIN), Mm(self.y), &self.font);
+        }
+    }
+
+    fn paragraph(&mut self, text: &str, font_size: f32) {
+        for line in text.lines() {
+            for wrapped in wrap(line, font_size, PAGE_WIDTH - MARGIN * 2.0) {
+                self.line(&wrapped, font_size);
+            }
+        }
+    }
+}
+
+pub fn render(note: &Note, font_path: &Path, dest: &Path) -> Result<(), AppError> {
+    let title = if note.title.trim().is_empty() {
+        "Untitled"
+    } else {
+        note.title.trim()
+    };
+    let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+    // 字体以子集形式嵌入 PDF，没有安装该字体的设备也能正常显示
+    let font_file = File::open(font_path).map_err(AppError::io("Failed to open PDF font"))?;
+    let font = doc
+        .add_external_font(font_file)
+        .map_err(AppError::serialization("Failed to load PDF font"))?;
+
+    let layer = doc.get_page(page).get_layer(layer);
+    let mut writer = Writer {
+        doc: &doc,
+        font,
+        layer,
+        y: PAGE_HEIGHT - MARGIN,
+    };
+    writer.paragraph(title, TITLE_SIZE);
+    writer.line("", BODY_SIZE);
+    writer.paragraph(&note.body, BODY_SIZE);
+
+    let file = File::create(dest).map_err(AppError::io("Failed to create PDF"))?;
+    doc.save(&mut BufWriter::new(file))
+        .map_err(AppError::serialization("Failed to write PDF"))
+}
@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// 可执行文件旁存在该文件时进入便携模式
+pub const MARKER_FILE: &str = "portable.txt";
+const DATA_DIR: &str = "data";
+
+// 便携模式下所有数据（配置、笔记、日志等）保存在可执行文件旁的 data/ 目录；
+// 非便携模式返回 None。结果在首次调用时确定，运行期间不变
+pub fn data_dir() -> Option<PathBuf> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let exe = std::env::current_exe().ok()?;
+        let dir = exe.parent()?;
+        dir.join(MARKER_FILE).is_file().then(|| dir.join(DATA_DIR))
+    })
+    .clone()
+}
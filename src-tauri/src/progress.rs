@@ -0,0 +1,38 @@
+use serde::Serialize;
+use tauri::Emitter;
+
+// 批量导入时每处理 BATCH_SIZE 条发出一次 import-progress，结束时发出 import-complete
+pub const PROGRESS_EVENT: &str = "import-progress";
+pub const COMPLETE_EVENT: &str = "import-complete";
+pub const BATCH_SIZE: usize = 50;
+
+#[derive(Serialize, Clone)]
+pub struct ImportProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ImportComplete {
+    pub total: usize,
+}
+
+// 分批追加导入的条目并发出进度；进度事件发送失败只记录日志
+pub fn extend_in_batches<T>(app_handle: &tauri::AppHandle, target: &mut Vec<T>, items: Vec<T>) {
+    let total = items.len();
+    let mut done = 0;
+    let mut items = items.into_iter();
+    while done < total {
+        target.extend(items.by_ref().take(BATCH_SIZE));
+        done = (done + BATCH_SIZE).min(total);
+        if let Err(e) = app_handle.emit(PROGRESS_EVENT, ImportProgress { done, total }) {
+            tracing::warn!("Failed to emit import progress: {}", e);
+        }
+    }
+}
+
+pub fn complete(app_handle: &tauri::AppHandle, total: usize) {
+    if let Err(e) = app_handle.emit(COMPLETE_EVENT, ImportComplete { total }) {
+        tracing::warn!("Failed to emit import complete: {}", e);
+    }
+}
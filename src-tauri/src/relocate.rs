@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+// 随存储目录迁移的数据文件与目录；config.json 等应用配置固定在 app_data_dir，不随之移动
+const DATA_FILES: [&str; 7] = [
+    "notes.json",
+    crate::checksum::CHECKSUM_FILE,
+    "tasks.json",
+    crate::archive::ARCHIVE_FILE,
+    "trash.json",
+    "reminders.json",
+    crate::sqlite::DB_FILE,
+];
+const DATA_DIRS: [&str; 4] = [
+    "backups",
+    crate::note_files::NOTES_DIR,
+    crate::markdown_files::NOTES_MD_DIR,
+    crate::attachments::ATTACHMENTS_DIR,
+];
+
+// 当前目录中实际存在的数据文件（相对路径），目录递归展开；降级到临时目录时也按此复制数据
+pub fn collect(from: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let mut files: Vec<PathBuf> = DATA_FILES
+        .iter()
+        .map(PathBuf::from)
+        .filter(|file| from.join(file).is_file())
+        .collect();
+    let mut pending: Vec<PathBuf> = DATA_DIRS
+        .iter()
+        .map(PathBuf::from)
+        .filter(|dir| from.join(dir).is_dir())
+        .collect();
+    while let Some(dir) = pending.pop() {
+        let entries =
+            fs::read_dir(from.join(&dir)).map_err(AppError::io("Failed to list data files"))?;
+        for entry in entries {
+            let entry = entry.map_err(AppError::io("Failed to list data files"))?;
+            let path = dir.join(entry.file_name());
+            if entry.path().is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+// 复制后逐字节比对
+fn copy_verified(source: &Path, target: &Path) -> std::io::Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(source, target)?;
+    if fs::read(source)? != fs::read(target)? {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "copy does not match the original",
+        ));
+    }
+    Ok(())
+}
+
+// 把数据复制到 to 并校验；任一文件失败时删除已复制的文件并返回失败清单，原目录保持不变
+pub fn copy_all(from: &Path, to: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let files = collect(from)?;
+    // 目标目录已有数据时拒绝迁移，避免覆盖
+    if let Some(existing) = files.iter().find(|file| to.join(file).exists()) {
+        return Err(AppError::InvalidInput(format!(
+            "Target directory already contains {}",
+            existing.display()
+        )));
+    }
+
+    let mut copied = Vec::new();
+    let mut failed = Vec::new();
+    for file in &files {
+        match copy_verified(&from.join(file), &to.join(file)) {
+            Ok(()) => copied.push(file.clone()),
+            Err(e) => failed.push(format!("{} ({})", file.display(), e)),
+        }
+    }
+    if !failed.is_empty() {
+        for file in &copied {
+            let _ = fs::remove_file(to.join(file));
+        }
+        return Err(AppError::Platform(format!("Failed to move: {}", failed.join(", "))));
+    }
+    Ok(files)
+}
+
+// 迁移完成后删除原文件；此时数据已在新目录中，删除失败只记录日志
+pub fn remove_originals(from: &Path, files: &[PathBuf]) {
+    for file in files {
+        if let Err(e) = fs::remove_file(from.join(file)) {
+            tracing::warn!("Failed to remove {}: {}", file.display(), e);
+        }
+    }
+    // 只删除已清空的目录（由深到浅，如 attachments/<id>），期间新写入的文件保留
+    let mut dirs: Vec<&Path> = files
+        .iter()
+        .filter_map(|file| file.parent())
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .collect();
+    dirs.sort_by(|a, b| b.components().count().cmp(&a.components().count()).then(a.cmp(b)));
+    dirs.dedup();
+    for dir in dirs {
+        let _ = fs::remove_dir(from.join(dir));
+    }
+    for dir in DATA_DIRS {
+        let _ = fs::remove_dir(from.join(dir));
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::crypto::PassphraseState;
+use crate::error::AppError;
+
+// 后台扫描间隔
+pub const SCAN_INTERVAL: Duration = Duration::from_secs(60);
+// 默认提前提醒的分钟数
+pub const DEFAULT_LEAD_MINUTES: u32 = 5;
+
+// 已提醒过的记录，键为 "<任务 id>@<due_at>" 或 "note:<笔记 id>@<remind_at>"，时间变更后会重新提醒；
+// 保存在 reminders.json 中，重启后不会重复提醒
+#[derive(Serialize, Deserialize, Default)]
+struct ReminderState {
+    notified: BTreeSet<String>,
+}
+
+fn load_state(app_dir: &Path) -> ReminderState {
+    fs::read_to_string(app_dir.join("reminders.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(app_dir: &Path, state: &ReminderState) -> Result<(), AppError> {
+    let contents = serde_json::to_string(state)
+        .map_err(AppError::serialization("Failed to serialize reminders"))?;
+    crate::write_atomic(&app_dir.join("reminders.json"), contents.as_bytes())
+        .map_err(AppError::io("Failed to save reminders"))
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        if let Err(e) = scan(&app_handle) {
+            tracing::warn!("Failed to scan reminders: {}", e);
+        }
+        std::thread::sleep(SCAN_INTERVAL);
+    });
+}
+
+// 笔记提醒的记录键前缀，与任务的记录区分
+const NOTE_KEY_PREFIX: &str = "note:";
+
+fn notify(app_handle: &tauri::AppHandle, title: &str, body: &str) -> bool {
+    match app_handle.notification().builder().title(title).body(body).show() {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("Failed to show notification: {}", e);
+            false
+        }
+    }
+}
+
+fn scan(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let app_dir = crate::storage_dir(app_handle)?;
+    let mut state = load_state(&app_dir);
+    let mut pending = BTreeSet::new();
+    let mut changed = scan_tasks(app_handle, &app_dir, &mut state, &mut pending)?;
+    match scan_notes(app_handle, &app_dir, &mut state, &mut pending) {
+        Ok(notified) => changed |= notified,
+        // 笔记暂时无法读取（如已加密尚未解锁）时保留已有记录，避免之后重复提醒
+        Err(e) => {
+            if !matches!(e, AppError::Locked) {
+                tracing::warn!("Failed to scan note reminders: {}", e);
+            }
+            pending.extend(
+                state
+                    .notified
+                    .iter()
+                    .filter(|key| key.starts_with(NOTE_KEY_PREFIX))
+                    .cloned(),
+            );
+        }
+    }
+
+    // 清理已完成、已删除或改期的任务与笔记的记录
+    let before = state.notified.len();
+    state.notified.retain(|key| pending.contains(key));
+    changed |= state.notified.len() != before;
+
+    if changed {
+        save_state(&app_dir, &state)?;
+    }
+    Ok(())
+}
+
+// 对即将到期（或已逾期）且尚未提醒过的未完成任务发出系统通知，返回是否有新的提醒
+fn scan_tasks(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    state: &mut ReminderState,
+    pending: &mut BTreeSet<String>,
+) -> Result<bool, AppError> {
+    let lead_minutes = crate::config::load(app_handle)
+        .reminder_lead_minutes
+        .unwrap_or(DEFAULT_LEAD_MINUTES);
+    let horizon = chrono::Utc::now() + chrono::Duration::minutes(i64::from(lead_minutes));
+    let tasks = crate::tasks::parse(&crate::read_tasks_json(app_handle, app_dir)?)?;
+
+    let mut changed = false;
+    for task in tasks.iter().filter(|task| !task.completed) {
+        let Some(due_at) = task.due_at.as_deref() else {
+            continue;
+        };
+        let Some(due) = crate::timestamp::parse(due_at) else {
+            continue;
+        };
+        let key = format!("{}@{}", task.id, due_at);
+        pending.insert(key.clone());
+        if due > horizon || state.notified.contains(&key) {
+            continue;
+        }
+
+        let title = if due <= chrono::Utc::now() { "任务已到期" } else { "任务即将到期" };
+        if notify(app_handle, title, &task.text) {
+            state.notified.insert(key);
+            changed = true;
+        }
+    }
+    Ok(changed)
+}
+
+// 对提醒时间已到且尚未提醒过的笔记发出系统通知，返回是否有新的提醒
+fn scan_notes(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    state: &mut ReminderState,
+    pending: &mut BTreeSet<String>,
+) -> Result<bool, AppError> {
+    let passphrase = app_handle.state::<PassphraseState>();
+    if crate::config::load(app_handle).encryption_enabled && passphrase.get().is_none() {
+        return Err(AppError::Locked);
+    }
+    // 只需要标题与提醒时间，不必每次扫描都解密 secure 笔记的正文
+    let notes =
+        crate::notes::parse(&crate::read_stored_notes_json(app_handle, app_dir, &passphrase)?)?;
+    let now = chrono::Utc::now();
+
+    let mut changed = false;
+    for note in &notes {
+        let Some(remind_at) = note.remind_at.as_deref() else {
+            continue;
+        };
+        let Some(due) = crate::timestamp::parse(remind_at) else {
+            continue;
+        };
+        let key = format!("{}{}@{}", NOTE_KEY_PREFIX, note.id, remind_at);
+        pending.insert(key.clone());
+        if due > now || state.notified.contains(&key) {
+            continue;
+        }
+
+        let title = note.title.trim();
+        let body = if title.is_empty() && note.secure {
+            crate::secure_notes::LOCKED_BODY
+        } else if title.is_empty() {
+            note.body.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default()
+        } else {
+            title
+        };
+        if notify(app_handle, "笔记提醒", body) {
+            state.notified.insert(key);
+            changed = true;
+        }
+    }
+    Ok(changed)
+}
@@ -0,0 +1,20 @@
+use std::ops::DerefMut;
+
+use tauri::async_runtime::Mutex;
+use tauri::Manager;
+
+// 串行执行 save_notes，避免重叠的保存交错写入；锁内记录最近一次写入的前端修订号
+#[derive(Default)]
+pub struct SaveQueue(pub Mutex<Option<u64>>);
+
+// 修订号比已写入的更旧时视为过期，直接丢弃；未带修订号的保存总是执行
+pub fn is_stale(last_written: Option<u64>, revision: Option<u64>) -> bool {
+    matches!((last_written, revision), (Some(last), Some(revision)) if revision < last)
+}
+
+// save_notes 之外写入笔记或任务的路径在读-改-写期间持有同一把锁，与排队中的保存串行执行。
+// 锁不可重入：持有锁时不能再调用 modify_notes、modify_tasks、persist_notes 等会加锁的函数
+pub fn lock(app_handle: &tauri::AppHandle) -> impl DerefMut<Target = Option<u64>> + '_ {
+    let queue = app_handle.state::<SaveQueue>().inner();
+    tauri::async_runtime::block_on(queue.0.lock())
+}
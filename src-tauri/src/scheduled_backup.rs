@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::crypto::PassphraseState;
+use crate::error::AppError;
+
+// 默认每隔多少小时做一次定时备份；设为 0 表示关闭
+pub const DEFAULT_INTERVAL_HOURS: u32 = 6;
+// 后台检查是否到期的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// 定时备份单独保留的份数（默认间隔下约为一周），不占用每次保存时的滚动备份名额
+const RETENTION: usize = 28;
+
+// 定时备份文件名形如 scheduled-notes-<unix-millis>.json.gz 与 scheduled-tasks-<unix-millis>.json.gz
+const NOTES_PREFIX: &str = "scheduled-notes-";
+const TASKS_PREFIX: &str = "scheduled-tasks-";
+const SUFFIX: &str = ".json.gz";
+
+fn interval_hours(app_handle: &tauri::AppHandle) -> u32 {
+    crate::config::load(app_handle)
+        .backup_interval_hours
+        .unwrap_or(DEFAULT_INTERVAL_HOURS)
+}
+
+fn parse_timestamp(filename: &str) -> Option<u64> {
+    filename
+        .strip_prefix(NOTES_PREFIX)
+        .or_else(|| filename.strip_prefix(TASKS_PREFIX))?
+        .strip_suffix(SUFFIX)?
+        .parse()
+        .ok()
+}
+
+pub fn is_backup_name(filename: &str) -> bool {
+    parse_timestamp(filename).is_some()
+}
+
+// 按时间倒序列出定时备份的时间戳（每个时间戳对应一组笔记与任务备份）
+fn list(dir: &Path) -> Vec<u64> {
+    let mut timestamps: Vec<u64> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| parse_timestamp(&entry.file_name().to_string_lossy()))
+        .collect();
+    timestamps.sort_unstable_by(|a, b| b.cmp(a));
+    timestamps.dedup();
+    timestamps
+}
+
+pub fn start(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        if let Err(e) = tick(&app_handle) {
+            tracing::warn!("Failed to create scheduled backup: {}", e);
+        }
+        std::thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+// 距上一次定时备份（以磁盘上的文件为准，重启后仍有效）超过间隔时备份一次
+fn tick(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let hours = interval_hours(app_handle);
+    if hours == 0 {
+        return Ok(());
+    }
+    let app_dir = crate::storage_dir(app_handle)?;
+    let dir = crate::backup::backups_dir(&app_dir);
+    let interval_ms = u64::from(hours) * 60 * 60 * 1000;
+    let now = crate::now_millis();
+    if list(&dir).first().is_some_and(|last| now.saturating_sub(*last) < interval_ms) {
+        return Ok(());
+    }
+
+    snapshot(app_handle, &app_dir, &dir, now)?;
+    prune(&dir);
+    Ok(())
+}
+
+fn snapshot(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    dir: &Path,
+    timestamp: u64,
+) -> Result<(), AppError> {
+    let passphrase = app_handle.state::<PassphraseState>();
+    let notes = crate::workspace::notes_contents(app_handle, app_dir, &passphrase)?;
+    let tasks = crate::read_tasks_json(app_handle, app_dir)?;
+
+    fs::create_dir_all(dir).map_err(AppError::io("Failed to create backups directory"))?;
+    let notes_file = dir.join(format!("{}{}{}", NOTES_PREFIX, timestamp, SUFFIX));
+    crate::backup::write_backup(&notes_file, &notes)
+        .map_err(AppError::io("Failed to back up notes"))?;
+    let tasks_file = dir.join(format!("{}{}{}", TASKS_PREFIX, timestamp, SUFFIX));
+    crate::backup::write_backup(&tasks_file, tasks.as_bytes())
+        .map_err(AppError::io("Failed to back up tasks"))
+}
+
+fn prune(dir: &Path) {
+    for timestamp in list(dir).into_iter().skip(RETENTION) {
+        for prefix in [NOTES_PREFIX, TASKS_PREFIX] {
+            let _ = fs::remove_file(dir.join(format!("{}{}{}", prefix, timestamp, SUFFIX)));
+        }
+    }
+}
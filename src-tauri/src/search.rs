@@ -0,0 +1,103 @@
+use serde::Serialize;
+
+use crate::notes::Note;
+use crate::tasks::Task;
+
+// 片段中命中部分的前后标记
+pub const MATCH_START: &str = "[[";
+pub const MATCH_END: &str = "]]";
+
+// 片段中命中位置前后保留的字符数
+const SNIPPET_CONTEXT: usize = 30;
+
+#[derive(Serialize)]
+pub struct SearchHit {
+    pub id: String,
+    // "title" 或 "body"
+    pub field: &'static str,
+    pub snippet: String,
+}
+
+// 不区分大小写的子串查找，返回命中部分的字节范围
+fn find_case_insensitive(haystack: &str, needle: &[char]) -> Option<(usize, usize)> {
+    let chars: Vec<(usize, char)> = haystack.char_indices().collect();
+    let last_start = chars.len().checked_sub(needle.len())?;
+    let start = (0..=last_start).find(|&start| {
+        chars[start..start + needle.len()]
+            .iter()
+            .zip(needle)
+            .all(|((_, a), b)| a.to_lowercase().eq(b.to_lowercase()))
+    })?;
+    let end = chars
+        .get(start + needle.len())
+        .map_or(haystack.len(), |(index, _)| *index);
+    Some((chars[start].0, end))
+}
+
+fn snippet(text: &str, start: usize, end: usize) -> String {
+    let before: Vec<char> = text[..start].chars().collect();
+    let after: Vec<char> = text[end..].chars().collect();
+    let before_start = before.len().saturating_sub(SNIPPET_CONTEXT);
+    let after_end = after.len().min(SNIPPET_CONTEXT);
+
+    let mut snippet = String::new();
+    if before_start > 0 {
+        snippet.push('…');
+    }
+    snippet.extend(&before[before_start..]);
+    snippet.push_str(MATCH_START);
+    snippet.push_str(&text[start..end]);
+    snippet.push_str(MATCH_END);
+    snippet.extend(&after[..after_end]);
+    if after_end < after.len() {
+        snippet.push('…');
+    }
+    // 片段用于单行展示
+    snippet.replace('\n', " ")
+}
+
+// 标题命中排在正文命中之前，同类命中保持笔记原有顺序
+pub fn search(notes: &[Note], query: &str) -> Vec<SearchHit> {
+    let needle: Vec<char> = query.trim().chars().collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut title_hits = Vec::new();
+    let mut body_hits = Vec::new();
+    for note in notes {
+        if let Some((start, end)) = find_case_insensitive(&note.title, &needle) {
+            title_hits.push(SearchHit {
+                id: note.id.clone(),
+                field: "title",
+                snippet: snippet(&note.title, start, end),
+            });
+        }
+        if let Some((start, end)) = find_case_insensitive(&note.body, &needle) {
+            body_hits.push(SearchHit {
+                id: note.id.clone(),
+                field: "body",
+                snippet: snippet(&note.body, start, end),
+            });
+        }
+    }
+
+    title_hits.extend(body_hits);
+    title_hits
+}
+
+// 标题（text）不区分大小写地包含 query 的任务，query 为空时匹配全部；only_incomplete 时排除已完成的。
+// 按截止时间从早到晚排列（没有截止时间的排在最后），同一截止时间按优先级从高到低
+pub fn search_tasks(tasks: Vec<Task>, query: &str, only_incomplete: bool) -> Vec<Task> {
+    let needle: Vec<char> = query.trim().chars().collect();
+    let mut matched: Vec<Task> = tasks
+        .into_iter()
+        .filter(|task| !(only_incomplete && task.completed))
+        .filter(|task| needle.is_empty() || find_case_insensitive(&task.text, &needle).is_some())
+        .collect();
+    matched.sort_by_key(|task| {
+        let due_at = task.due_at.as_deref().and_then(crate::timestamp::parse);
+        (due_at.is_none(), due_at, task.priority)
+    });
+    matched
+}
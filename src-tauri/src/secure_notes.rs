@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::error::AppError;
+use crate::notes;
+
+// 尚未输入口令时 secure 笔记返回的占位正文
+pub const LOCKED_BODY: &str = "🔒 locked";
+
+// secure 笔记在存储中的正文为 base64 编码的 crypto::encrypt 密文，标题等字段保持明文
+fn encrypt_body(passphrase: &str, body: &str) -> Result<String, AppError> {
+    Ok(STANDARD.encode(crate::crypto::encrypt(passphrase, body.as_bytes())?))
+}
+
+fn decrypt_body(passphrase: &str, body: &str) -> Result<String, AppError> {
+    let data = STANDARD
+        .decode(body.trim())
+        .map_err(|_| AppError::Encryption("Encrypted note body is malformed".to_string()))?;
+    let plaintext = crate::crypto::decrypt(passphrase, &data)?;
+    String::from_utf8(plaintext).map_err(AppError::serialization("Note is not valid UTF-8"))
+}
+
+// 存储中至少有一条 secure 笔记时，用其检查口令是否正确
+pub fn verify(stored_notes: &str, passphrase: &str) -> Result<(), AppError> {
+    let notes = notes::parse(stored_notes)?;
+    match notes.iter().find(|note| note.secure) {
+        Some(note) => decrypt_body(passphrase, &note.body).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+// 读取后：解密 secure 笔记的正文，未输入口令时以占位正文代替
+pub fn reveal(stored_notes: String, passphrase: Option<&str>) -> Result<String, AppError> {
+    let mut notes = notes::parse(&stored_notes)?;
+    if !notes.iter().any(|note| note.secure) {
+        return Ok(stored_notes);
+    }
+    for note in notes.iter_mut().filter(|note| note.secure) {
+        note.body = match passphrase {
+            Some(passphrase) => decrypt_body(passphrase, &note.body)?,
+            None => LOCKED_BODY.to_string(),
+        };
+    }
+    notes::to_json(&notes)
+}
+
+// 写入前：加密 secure 笔记的正文。正文仍是占位文本时（前端在解锁前加载的内容）
+// 沿用存储中已有的密文；stored 只在需要时才读取
+pub fn seal(
+    notes_json: &str,
+    passphrase: Option<&str>,
+    stored: impl FnOnce() -> Result<String, AppError>,
+) -> Result<String, AppError> {
+    let mut notes = notes::parse(notes_json)?;
+    if !notes.iter().any(|note| note.secure) {
+        return Ok(notes_json.to_string());
+    }
+
+    let needs_stored = notes.iter().any(|note| note.secure && note.body == LOCKED_BODY);
+    let stored_bodies: HashMap<String, String> = if needs_stored {
+        notes::parse(&stored()?)?
+            .into_iter()
+            .filter(|note| note.secure)
+            .map(|note| (note.id, note.body))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    for note in notes.iter_mut().filter(|note| note.secure) {
+        note.body = match (stored_bodies.get(&note.id), passphrase) {
+            (Some(body), _) if note.body == LOCKED_BODY => body.clone(),
+            (_, Some(passphrase)) => encrypt_body(passphrase, &note.body)?,
+            (_, None) => return Err(AppError::Locked),
+        };
+    }
+    notes::to_json(&notes)
+}
@@ -0,0 +1,200 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::Manager;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use crate::error::AppError;
+
+pub const TOGGLE_WINDOW: &str = "toggle_window";
+pub const QUIT: &str = "quit";
+pub const TOGGLE_ALWAYS_ON_TOP: &str = "toggle_always_on_top";
+pub const NOTE_FROM_CLIPBOARD: &str = "note_from_clipboard";
+pub const NEW_NOTE: &str = "new_note";
+
+// 各动作的默认快捷键，shortcuts.json 缺失或某项无效时使用
+const DEFAULTS: [(&str, &str); 5] = [
+    (TOGGLE_WINDOW, "CmdOrCtrl+M"),
+    (QUIT, "CmdOrCtrl+Q"),
+    (TOGGLE_ALWAYS_ON_TOP, "CmdOrCtrl+T"),
+    (NOTE_FROM_CLIPBOARD, "CmdOrCtrl+Alt+V"),
+    (NEW_NOTE, "CmdOrCtrl+N"),
+];
+
+// 显示在快捷键帮助面板中的说明
+fn description(action: &str) -> &'static str {
+    match action {
+        TOGGLE_WINDOW => "Show or hide the main window",
+        QUIT => "Quit the app",
+        TOGGLE_ALWAYS_ON_TOP => "Toggle always on top",
+        NOTE_FROM_CLIPBOARD => "Create a note from the clipboard",
+        NEW_NOTE => "Create a blank note",
+        _ => "",
+    }
+}
+
+#[derive(Serialize)]
+pub struct ShortcutInfo {
+    pub action: String,
+    pub accelerator: String,
+    pub description: String,
+}
+
+// 当前已注册的快捷键：动作名 -> 快捷键
+#[derive(Default)]
+pub struct Bindings(Mutex<HashMap<String, Shortcut>>);
+
+fn default_for(action: &str) -> Option<&'static str> {
+    DEFAULTS
+        .iter()
+        .find(|(name, _)| *name == action)
+        .map(|(_, accelerator)| *accelerator)
+}
+
+fn parse(accelerator: &str) -> Result<Shortcut, AppError> {
+    accelerator
+        .parse::<Shortcut>()
+        .map_err(|e| AppError::InvalidInput(format!("Invalid shortcut \"{}\": {}", accelerator, e)))
+}
+
+fn shortcuts_file(app_handle: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    Ok(crate::app_data_dir(app_handle)?.join("shortcuts.json"))
+}
+
+// 读取 shortcuts.json 并与默认值合并，忽略未知动作
+fn load_config(app_handle: &tauri::AppHandle) -> BTreeMap<String, String> {
+    let mut config: BTreeMap<String, String> = DEFAULTS
+        .iter()
+        .map(|(action, accelerator)| (action.to_string(), accelerator.to_string()))
+        .collect();
+
+    let saved: Option<BTreeMap<String, String>> = shortcuts_file(app_handle)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok());
+    for (action, accelerator) in saved.unwrap_or_default() {
+        if let Some(current) = config.get_mut(&action) {
+            *current = accelerator;
+        }
+    }
+    config
+}
+
+fn save_config(app_handle: &tauri::AppHandle, config: &BTreeMap<String, String>) -> Result<(), AppError> {
+    let app_dir = crate::app_data_dir(app_handle)?;
+    fs::create_dir_all(&app_dir).map_err(AppError::io("Failed to create app directory"))?;
+
+    let contents = serde_json::to_string(config)
+        .map_err(AppError::serialization("Failed to serialize shortcuts"))?;
+    crate::write_atomic(&shortcuts_file(app_handle)?, contents.as_bytes())
+        .map_err(AppError::io("Failed to save shortcuts"))
+}
+
+// 启动时注册所有快捷键；单个快捷键注册失败（如被其它应用占用）不影响其余快捷键
+pub fn register_all(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let mut bindings = HashMap::new();
+    for (action, accelerator) in load_config(app_handle) {
+        let shortcut = match parse(&accelerator) {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                tracing::warn!("{}, falling back to default", e);
+                parse(default_for(&action).unwrap_or_default())?
+            }
+        };
+        if let Err(e) = app_handle.global_shortcut().register(shortcut) {
+            tracing::warn!("Failed to register shortcut {}: {}", accelerator, e);
+            continue;
+        }
+        bindings.insert(action, shortcut);
+    }
+
+    *app_handle.state::<Bindings>().0.lock().unwrap() = bindings;
+    Ok(())
+}
+
+// 各动作当前的快捷键，与 register_all 读取同一份配置；无效的快捷键显示为注册时使用的默认值
+pub fn list(app_handle: &tauri::AppHandle) -> Vec<ShortcutInfo> {
+    load_config(app_handle)
+        .into_iter()
+        .map(|(action, accelerator)| {
+            let accelerator = match parse(&accelerator) {
+                Ok(_) => accelerator,
+                Err(_) => default_for(&action).unwrap_or_default().to_string(),
+            };
+            ShortcutInfo {
+                description: description(&action).to_string(),
+                action,
+                accelerator,
+            }
+        })
+        .collect()
+}
+
+// 重新读取 shortcuts.json：注销全部快捷键后重新注册
+pub fn reload(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    app_handle
+        .global_shortcut()
+        .unregister_all()
+        .map_err(AppError::platform("Failed to unregister shortcuts"))?;
+    register_all(app_handle)
+}
+
+pub fn action_for(app_handle: &tauri::AppHandle, shortcut: &Shortcut) -> Option<String> {
+    app_handle
+        .state::<Bindings>()
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(_, bound)| *bound == shortcut)
+        .map(|(action, _)| action.clone())
+}
+
+// 重新绑定某个动作：先注销旧快捷键再注册新快捷键，注册失败时恢复旧绑定
+pub fn set(app_handle: &tauri::AppHandle, action: &str, accelerator: &str) -> Result<(), AppError> {
+    if default_for(action).is_none() {
+        return Err(AppError::InvalidInput(format!("Unknown shortcut action: {}", action)));
+    }
+    let shortcut = parse(accelerator)?;
+
+    let state = app_handle.state::<Bindings>();
+    let mut bindings = state.0.lock().unwrap();
+    if bindings
+        .iter()
+        .any(|(other, bound)| other != action && *bound == shortcut)
+    {
+        return Err(AppError::InvalidInput(format!(
+            "Shortcut {} is already bound to another action",
+            accelerator
+        )));
+    }
+
+    let global_shortcut = app_handle.global_shortcut();
+    let previous = bindings.get(action).copied();
+    if previous != Some(shortcut) {
+        if let Some(previous) = previous {
+            global_shortcut
+                .unregister(previous)
+                .map_err(AppError::platform("Failed to unregister shortcut"))?;
+        }
+        if let Err(e) = global_shortcut.register(shortcut) {
+            if let Some(previous) = previous {
+                let _ = global_shortcut.register(previous);
+            }
+            return Err(AppError::Platform(format!(
+                "Failed to register shortcut {}: {}",
+                accelerator, e
+            )));
+        }
+    }
+
+    bindings.insert(action.to_string(), shortcut);
+    drop(bindings);
+
+    let mut config = load_config(app_handle);
+    config.insert(action.to_string(), accelerator.to_string());
+    save_config(app_handle, &config)
+}
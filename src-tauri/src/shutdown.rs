@@ -0,0 +1,63 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+
+// 关闭前发给前端的事件；前端完成最后一次保存后调用 close_ready 确认
+pub const FLUSH_EVENT: &str = "flush-before-close";
+
+// 前端无响应时最多等待的时长，超时后照常关闭
+pub const ACK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Default)]
+pub struct CloseState {
+    acked: Mutex<bool>,
+    acked_changed: Condvar,
+    // 关闭流程已开始，重复触发（如连按退出）时忽略
+    in_progress: AtomicBool,
+    // 前端已确认（或已超时），再次收到 CloseRequested 时直接放行
+    ready: AtomicBool,
+}
+
+// 通知前端做最后一次保存并等待确认，随后写入尚未落盘的防抖内容，最后执行 finish（关闭窗口或退出）
+pub fn begin(app_handle: &tauri::AppHandle, finish: impl FnOnce(&tauri::AppHandle) + Send + 'static) {
+    let state = app_handle.state::<CloseState>();
+    if state.in_progress.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    *state.acked.lock().unwrap() = false;
+    if let Err(e) = app_handle.emit(FLUSH_EVENT, ()) {
+        tracing::warn!("Failed to emit {}: {}", FLUSH_EVENT, e);
+    }
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        let state = app_handle.state::<CloseState>();
+        let acked = state.acked.lock().unwrap();
+        let (acked, result) = state
+            .acked_changed
+            .wait_timeout_while(acked, ACK_TIMEOUT, |acked| !*acked)
+            .unwrap();
+        drop(acked);
+        if result.timed_out() {
+            tracing::warn!("Frontend did not acknowledge {} in time, closing anyway", FLUSH_EVENT);
+        }
+
+        if let Err(e) = crate::autosave::flush(&app_handle) {
+            tracing::warn!("Failed to save notes: {}", e);
+        }
+        state.ready.store(true, Ordering::SeqCst);
+        finish(&app_handle);
+    });
+}
+
+pub fn acknowledge(app_handle: &tauri::AppHandle) {
+    let state = app_handle.state::<CloseState>();
+    *state.acked.lock().unwrap() = true;
+    state.acked_changed.notify_all();
+}
+
+pub fn is_ready(app_handle: &tauri::AppHandle) -> bool {
+    app_handle.state::<CloseState>().ready.load(Ordering::SeqCst)
+}
@@ -0,0 +1,47 @@
+use crate::error::AppError;
+use crate::notes;
+
+// 单条笔记正文的默认上限
+pub const DEFAULT_NOTE_BYTES: u64 = 1024 * 1024;
+// 全部笔记序列化后的默认上限
+pub const DEFAULT_TOTAL_BYTES: u64 = 20 * 1024 * 1024;
+
+// 保存前检查大小，避免误粘贴的大段内容让笔记文件膨胀、保存变慢；超出时返回 InvalidInput 并指明笔记。
+// 只检查正文相对 previous（存储中的上一版本）有变化的笔记，总大小只在保存使文件变大时检查，
+// 这样调低上限或导入了超限的笔记后，置顶、改颜色等不涉及这些笔记的修改仍能保存
+pub fn check(
+    app_handle: &tauri::AppHandle,
+    previous: &[notes::Note],
+    notes: &[notes::Note],
+) -> Result<(), AppError> {
+    let config = crate::config::load(app_handle);
+    let per_note = config.max_note_bytes.unwrap_or(DEFAULT_NOTE_BYTES);
+    let total = config.max_total_bytes.unwrap_or(DEFAULT_TOTAL_BYTES);
+
+    let changed = |note: &&notes::Note| {
+        !previous
+            .iter()
+            .any(|prior| prior.id == note.id && prior.body == note.body)
+    };
+    if let Some(note) = notes
+        .iter()
+        .filter(changed)
+        .find(|note| note.body.len() as u64 > per_note)
+    {
+        let name = if note.title.trim().is_empty() { &note.id } else { &note.title };
+        return Err(AppError::InvalidInput(format!(
+            "Note \"{}\" is {} bytes, over the per-note limit of {} bytes",
+            name,
+            note.body.len(),
+            per_note
+        )));
+    }
+    let size = notes::to_json(notes)?.len();
+    if size as u64 > total && size > notes::to_json(previous)?.len() {
+        return Err(AppError::InvalidInput(format!(
+            "Notes are {} bytes in total, over the limit of {} bytes",
+            size, total
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{params, Connection, Transaction};
+use serde_json::{Map, Value};
+
+use crate::error::AppError;
+use crate::notes::Note;
+
+pub const DB_FILE: &str = "sticky.db";
+
+const NOTE_COLUMNS: [&str; 5] = ["id", "title", "body", "created_at", "updated_at"];
+
+// 笔记表按固定列存储，其余字段（颜色等）以 JSON 形式存入 extra；
+// 任务结构由前端决定，整体存为 JSON。position 用于保持数组顺序
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS notes (
+        id TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        body TEXT NOT NULL,
+        created_at TEXT,
+        updated_at TEXT,
+        extra TEXT NOT NULL DEFAULT '{}'
+    );
+    CREATE TABLE IF NOT EXISTS tasks (
+        id TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+";
+
+// 打开数据库并确保表结构存在；若目录中仍有 notes.json / tasks.json，则导入并重命名为 .migrated
+fn open(app_dir: &Path) -> Result<Connection, AppError> {
+    fs::create_dir_all(app_dir).map_err(AppError::io("Failed to create app directory"))?;
+    let mut conn = Connection::open(app_dir.join(DB_FILE))?;
+    conn.execute_batch(SCHEMA)?;
+    migrate_json(&mut conn, app_dir)?;
+    Ok(conn)
+}
+
+// JSON 文件存在即视为尚未迁移的权威数据，覆盖数据库中的对应内容
+fn migrate_json(conn: &mut Connection, app_dir: &Path) -> Result<(), AppError> {
+    let notes_file = app_dir.join("notes.json");
+    let tasks_file = app_dir.join("tasks.json");
+    if !notes_file.exists() && !tasks_file.exists() {
+        return Ok(());
+    }
+
+    let tx = conn.transaction()?;
+    if notes_file.exists() {
+        let json = fs::read_to_string(&notes_file)
+            .map_err(AppError::io("Failed to load notes"))?;
+        let notes = crate::notes::from_envelope(&json)?;
+        replace_notes(&tx, &crate::notes::parse(&notes)?)?;
+    }
+    if tasks_file.exists() {
+        let json = fs::read_to_string(&tasks_file)
+            .map_err(AppError::io("Failed to load tasks"))?;
+        let tasks: Vec<Value> =
+            serde_json::from_str(&json).map_err(AppError::serialization("Failed to parse tasks"))?;
+        replace_tasks(&tx, &tasks)?;
+    }
+    tx.commit()?;
+
+    // 事务提交成功后再重命名旧文件
+    for file in [notes_file, tasks_file] {
+        if file.exists() {
+            let mut migrated = file.clone().into_os_string();
+            migrated.push(".migrated");
+            fs::rename(&file, migrated)
+                .map_err(AppError::io("Failed to rename migrated file"))?;
+        }
+    }
+    Ok(())
+}
+
+// 以数组为准：逐条 upsert，并删除数组中已不存在的行
+fn replace_notes(tx: &Transaction, notes: &[Note]) -> Result<(), AppError> {
+    let mut upsert = tx
+        .prepare_cached(
+            "INSERT INTO notes (id, position, title, body, created_at, updated_at, extra)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(id) DO UPDATE SET
+                position = excluded.position,
+                title = excluded.title,
+                body = excluded.body,
+                created_at = excluded.created_at,
+                updated_at = excluded.updated_at,
+                extra = excluded.extra",
+        )?;
+
+    let mut ids = Vec::with_capacity(notes.len());
+    for (position, note) in notes.iter().enumerate() {
+        // 主键不能为空，缺失 id 的笔记在入库时分配新 id
+        let id = if note.id.is_empty() {
+            uuid::Uuid::new_v4().to_string()
+        } else {
+            note.id.clone()
+        };
+        let extra = extra_fields(note)?.to_string();
+        upsert
+            .execute(params![
+                id,
+                position as i64,
+                note.title,
+                note.body,
+                note.created_at,
+                note.updated_at,
+                extra
+            ])?;
+        ids.push(id);
+    }
+
+    tx.execute(
+        "DELETE FROM notes WHERE id NOT IN (SELECT value FROM json_each(?1))",
+        params![Value::from(ids).to_string()],
+    )?;
+    Ok(())
+}
+
+// 笔记中除固定列以外的全部字段
+fn extra_fields(note: &Note) -> Result<Value, AppError> {
+    let mut value =
+        serde_json::to_value(note).map_err(AppError::serialization("Failed to serialize notes"))?;
+    if let Some(fields) = value.as_object_mut() {
+        for column in NOTE_COLUMNS {
+            fields.remove(column);
+        }
+    }
+    Ok(value)
+}
+
+fn replace_tasks(tx: &Transaction, tasks: &[Value]) -> Result<(), AppError> {
+    let mut upsert = tx
+        .prepare_cached(
+            "INSERT INTO tasks (id, position, data) VALUES (?1, ?2, ?3)
+             ON CONFLICT(id) DO UPDATE SET position = excluded.position, data = excluded.data",
+        )?;
+
+    let mut ids = Vec::with_capacity(tasks.len());
+    for (position, task) in tasks.iter().enumerate() {
+        let id = match task.get("id") {
+            Some(Value::String(id)) if !id.is_empty() => id.clone(),
+            Some(Value::Number(id)) => id.to_string(),
+            _ => uuid::Uuid::new_v4().to_string(),
+        };
+        upsert
+            .execute(params![id, position as i64, task.to_string()])?;
+        ids.push(id);
+    }
+
+    tx.execute(
+        "DELETE FROM tasks WHERE id NOT IN (SELECT value FROM json_each(?1))",
+        params![Value::from(ids).to_string()],
+    )?;
+    Ok(())
+}
+
+pub fn save_notes(app_dir: &Path, notes: &[Note]) -> Result<(), AppError> {
+    let mut conn = open(app_dir)?;
+    let tx = conn.transaction()?;
+    replace_notes(&tx, notes)?;
+    Ok(tx.commit()?)
+}
+
+pub fn load_notes(app_dir: &Path) -> Result<Vec<Note>, AppError> {
+    let conn = open(app_dir)?;
+    let mut stmt = conn
+        .prepare("SELECT id, title, body, created_at, updated_at, extra FROM notes ORDER BY position")?;
+    let rows = stmt
+        .query_map([], |row| {
+            let extra: String = row.get(5)?;
+            let mut fields = serde_json::from_str::<Map<String, Value>>(&extra).unwrap_or_default();
+            fields.insert("id".into(), row.get::<_, String>(0)?.into());
+            fields.insert("title".into(), row.get::<_, String>(1)?.into());
+            fields.insert("body".into(), row.get::<_, String>(2)?.into());
+            fields.insert("created_at".into(), row.get::<_, Option<String>>(3)?.into());
+            fields.insert("updated_at".into(), row.get::<_, Option<String>>(4)?.into());
+            Ok(Value::Object(fields))
+        })?;
+
+    let mut notes = Vec::new();
+    for fields in rows {
+        let fields = fields?;
+        let note = serde_json::from_value(fields)
+            .map_err(AppError::serialization("Failed to parse notes"))?;
+        notes.push(note);
+    }
+    Ok(notes)
+}
+
+pub fn save_tasks(app_dir: &Path, tasks: &[Value]) -> Result<(), AppError> {
+    let mut conn = open(app_dir)?;
+    let tx = conn.transaction()?;
+    replace_tasks(&tx, tasks)?;
+    Ok(tx.commit()?)
+}
+
+pub fn load_tasks(app_dir: &Path) -> Result<Vec<Value>, AppError> {
+    let conn = open(app_dir)?;
+    let mut stmt = conn
+        .prepare("SELECT data FROM tasks ORDER BY position")?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))?;
+
+    let mut tasks = Vec::new();
+    for data in rows {
+        let data = data?;
+        let task = serde_json::from_str(&data)
+            .map_err(AppError::serialization("Failed to parse tasks"))?;
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
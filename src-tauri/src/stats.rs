@@ -0,0 +1,44 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct NoteStats {
+    pub words: usize,
+    pub characters: usize,
+    pub characters_no_whitespace: usize,
+    pub lines: usize,
+}
+
+// 中日文字（汉字、假名）之间不用空格分词，每个字计为一个词
+fn is_ideographic(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'     // 平假名、片假名
+        | '\u{3400}'..='\u{4DBF}'   // 扩展 A
+        | '\u{4E00}'..='\u{9FFF}'   // 基本汉字
+        | '\u{F900}'..='\u{FAFF}'   // 兼容汉字
+        | '\u{20000}'..='\u{2FA1F}' // 扩展 B 及之后
+    )
+}
+
+// 其它文字按空白分词，只由标点组成的片段不计为词
+pub fn count(body: &str) -> NoteStats {
+    let mut words = 0;
+    let mut in_word = false;
+    for c in body.chars() {
+        if is_ideographic(c) {
+            words += 1;
+            in_word = false;
+        } else if c.is_whitespace() {
+            in_word = false;
+        } else if c.is_alphanumeric() && !in_word {
+            words += 1;
+            in_word = true;
+        }
+    }
+
+    NoteStats {
+        words,
+        characters: body.chars().count(),
+        characters_no_whitespace: body.chars().filter(|c| !c.is_whitespace()).count(),
+        lines: body.lines().count(),
+    }
+}
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::config::{self, StorageBackend};
+use crate::crypto::PassphraseState;
+use crate::error::AppError;
+
+// 数据目录与数据文件准备就绪后发出，前端收到后再进行首次加载
+pub const READY_EVENT: &str = "storage-ready";
+
+// 紧随 storage-ready 发出，载荷为应聚焦的笔记 id
+pub const RESTORE_ACTIVE_EVENT: &str = "restore-active-note";
+
+#[derive(Serialize, Clone)]
+pub struct ReadyEvent {
+    pub path: String,
+}
+
+// 准备完成后记录的事件内容；为 None 表示尚未就绪
+#[derive(Default)]
+pub struct StorageReady(Mutex<Option<ReadyEvent>>);
+
+// 启动时调用：创建数据目录，缺失的 notes.json / tasks.json 写入空数组，然后发出 storage-ready。
+// 创建失败时同样发出事件，前端随后的加载会返回具体错误，而不是一直等待
+pub fn prepare(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let app_dir = crate::storage_dir(app_handle)?;
+    let created = create_missing(app_handle, &app_dir);
+
+    let event = ReadyEvent {
+        path: app_dir.display().to_string(),
+    };
+    *app_handle.state::<StorageReady>().0.lock().unwrap() = Some(event);
+    notify(app_handle);
+    created
+}
+
+// 笔记已加密时无法在没有口令的情况下写入，notes.json 留待首次保存时创建
+fn create_missing(app_handle: &tauri::AppHandle, app_dir: &Path) -> Result<(), AppError> {
+    fs::create_dir_all(app_dir).map_err(AppError::io("Failed to create app directory"))?;
+
+    let config = config::load(app_handle);
+    if config.storage_backend == StorageBackend::Json
+        && !config.encryption_enabled
+        && !app_dir.join("notes.json").exists()
+    {
+        let passphrase = app_handle.state::<PassphraseState>();
+        crate::write_notes_json(app_handle, app_dir, &passphrase, "[]")?;
+    }
+    if config.storage_backend != StorageBackend::Sqlite && !app_dir.join("tasks.json").exists() {
+        crate::write_tasks_json(app_handle, app_dir, &serde_json::Value::Array(Vec::new()))?;
+    }
+    Ok(())
+}
+
+// 已就绪时向前端发出 storage-ready 与 restore-active-note；启动时与页面每次加载完成后调用
+pub fn notify(app_handle: &tauri::AppHandle) {
+    let event = app_handle.state::<StorageReady>().0.lock().unwrap().clone();
+    if let Some(event) = event {
+        if let Err(e) = app_handle.emit(READY_EVENT, event) {
+            tracing::warn!("Failed to emit storage-ready: {}", e);
+        }
+        if let Some(id) = active_note(app_handle) {
+            if let Err(e) = app_handle.emit(RESTORE_ACTIVE_EVENT, id) {
+                tracing::warn!("Failed to emit restore-active-note: {}", e);
+            }
+        }
+    }
+}
+
+// 上次聚焦的笔记；该笔记已被删除时改为最近修改的笔记。未记录过、没有笔记或笔记已加密且未解锁时为 None
+fn active_note(app_handle: &tauri::AppHandle) -> Option<String> {
+    let saved = config::load(app_handle).active_note?;
+    let app_dir = crate::storage_dir(app_handle).ok()?;
+    let passphrase = app_handle.state::<PassphraseState>();
+    // secure 笔记的 id 为明文，读取存储中的原始内容即可
+    let stored = crate::read_notes_json_from_backend(app_handle, &app_dir, &passphrase).ok()?;
+    let notes = crate::notes::parse(&stored).ok()?;
+    if notes.iter().any(|note| note.id == saved) {
+        return Some(saved);
+    }
+    crate::notes::recent(notes, 1).pop().map(|note| note.id)
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use crate::error::AppError;
+use crate::notes::Note;
+
+// 单个标签的最大字符数（不含开头的 #）
+pub const MAX_TAG_CHARS: usize = 50;
+
+// 比较用的规范形式：去掉开头的 # 并忽略大小写，#Work 与 work 视为同一标签
+fn normalize(tag: &str) -> String {
+    tag.trim_start_matches('#').to_lowercase()
+}
+
+// 保存前校验：标签不能为空、不能含空白字符、不超过 MAX_TAG_CHARS，同一笔记内不能重复
+pub fn validate(notes: &[Note]) -> Result<(), AppError> {
+    for note in notes {
+        let mut seen = Vec::with_capacity(note.tags.len());
+        for tag in &note.tags {
+            let name = tag.trim_start_matches('#');
+            let invalid = |reason: &str| {
+                AppError::InvalidInput(format!(
+                    "Invalid tag \"{}\" on note {}: {}",
+                    tag, note.id, reason
+                ))
+            };
+            if name.is_empty() {
+                return Err(invalid("empty tag"));
+            }
+            if name.chars().any(char::is_whitespace) {
+                return Err(invalid("tags cannot contain whitespace"));
+            }
+            if name.chars().count() > MAX_TAG_CHARS {
+                return Err(invalid("tag is too long"));
+            }
+            let normalized = normalize(tag);
+            if seen.contains(&normalized) {
+                return Err(invalid("duplicate tag"));
+            }
+            seen.push(normalized);
+        }
+    }
+    Ok(())
+}
+
+pub fn filter(notes: Vec<Note>, tag: &str) -> Vec<Note> {
+    let tag = normalize(tag.trim());
+    notes
+        .into_iter()
+        .filter(|note| note.tags.iter().any(|candidate| normalize(candidate) == tag))
+        .collect()
+}
+
+// 所有标签及使用次数，按次数从多到少、再按名称排序；大小写不同的写法合并计数，显示最先出现的写法
+pub fn count(notes: &[Note]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    let mut index_by_tag: HashMap<String, usize> = HashMap::new();
+    for tag in notes.iter().flat_map(|note| &note.tags) {
+        let display = tag.trim_start_matches('#');
+        match index_by_tag.get(&normalize(tag)) {
+            Some(&index) => counts[index].1 += 1,
+            None => {
+                index_by_tag.insert(normalize(tag), counts.len());
+                counts.push((display.to_string(), 1));
+            }
+        }
+    }
+    counts.sort_by(|(a_tag, a_count), (b_tag, b_count)| {
+        b_count.cmp(a_count).then_with(|| a_tag.to_lowercase().cmp(&b_tag.to_lowercase()))
+    });
+    counts
+}
@@ -0,0 +1,312 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Days, Months, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::AppError;
+use crate::notes::null_as_default;
+
+// 任务的已知字段；前端的其它字段（createdAt、计时状态等）原样保存在 extra 中
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Task {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub id: String,
+    // 前端使用 text 字段保存任务标题
+    #[serde(default, alias = "title", deserialize_with = "null_as_default")]
+    pub text: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub completed: bool,
+    // ISO-8601 截止时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    // 未知的取值在反序列化时即报错，save_tasks 会拒绝保存
+    #[serde(default, deserialize_with = "null_as_default", skip_serializing_if = "Priority::is_none")]
+    pub priority: Priority,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+// 声明顺序即排序顺序：high 最先，none 最后
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+    #[default]
+    None,
+}
+
+impl Priority {
+    fn is_none(&self) -> bool {
+        *self == Priority::None
+    }
+}
+
+impl Recurrence {
+    fn advance_date(self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::Daily => date.checked_add_days(Days::new(1)),
+            Recurrence::Weekly => date.checked_add_days(Days::new(7)),
+            // chrono 会把月末对齐到目标月份的最后一天（如 1 月 31 日 -> 2 月 28/29 日）
+            Recurrence::Monthly => date.checked_add_months(Months::new(1)),
+        }
+    }
+
+    fn advance_datetime(self, datetime: NaiveDateTime) -> Option<NaiveDateTime> {
+        let date = self.advance_date(datetime.date())?;
+        Some(date.and_time(datetime.time()))
+    }
+}
+
+// 按原有格式计算下一次截止时间：仅日期、带时区、本地时间三种写法分别保持
+fn next_due(due_at: &str, recurrence: Recurrence) -> Option<String> {
+    let due_at = due_at.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(due_at, "%Y-%m-%d") {
+        return recurrence
+            .advance_date(date)
+            .map(|date| date.format("%Y-%m-%d").to_string());
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(due_at) {
+        let next = recurrence.advance_datetime(datetime.naive_local())?;
+        return next
+            .and_local_timezone(*datetime.offset())
+            .single()
+            .map(|next| next.to_rfc3339());
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(due_at, format).ok())
+        .and_then(|datetime| recurrence.advance_datetime(datetime))
+        .map(|next| next.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+pub fn parse(tasks_json: &str) -> Result<Vec<Task>, AppError> {
+    serde_json::from_str(tasks_json).map_err(AppError::serialization("Failed to parse tasks"))
+}
+
+// 按优先级从高到低排序，同一优先级内按截止时间从早到晚，没有截止时间的排在最后；其余保持原有顺序
+pub fn sort_by_priority(tasks: &mut [Task]) {
+    tasks.sort_by_key(|task| {
+        let due_at = task.due_at.as_deref().and_then(crate::timestamp::parse);
+        (task.priority, due_at.is_none(), due_at)
+    });
+}
+
+// 截止时间已过且未完成的任务
+pub fn overdue(tasks: Vec<Task>) -> Vec<Task> {
+    let now = chrono::Utc::now();
+    tasks
+        .into_iter()
+        .filter(|task| !task.completed)
+        .filter(|task| {
+            task.due_at
+                .as_deref()
+                .and_then(crate::timestamp::parse)
+                .is_some_and(|due_at| due_at < now)
+        })
+        .collect()
+}
+
+// 保存前校验：必须是任务数组，每个元素都能解析为任务且带有 id，截止时间若存在必须是合法的 ISO-8601。
+// 返回校验后的任务，由调用方重新序列化写盘，保证磁盘上的文件结构规整
+pub fn validate(tasks: Value) -> Result<Vec<Task>, AppError> {
+    let Value::Array(tasks) = tasks else {
+        return Err(AppError::InvalidInput("Tasks must be an array".to_string()));
+    };
+    tasks
+        .into_iter()
+        .enumerate()
+        .map(|(index, task)| {
+            let task: Task = serde_json::from_value(task).map_err(|e| {
+                AppError::InvalidInput(format!("Invalid task at index {}: {}", index, e))
+            })?;
+            if task.id.is_empty() {
+                return Err(AppError::InvalidInput(format!(
+                    "Invalid task at index {}: missing id",
+                    index
+                )));
+            }
+            if let Some(due_at) = &task.due_at {
+                if crate::timestamp::parse(due_at).is_none() {
+                    return Err(AppError::InvalidInput(format!(
+                        "Invalid task at index {}: due_at is not ISO-8601: {}",
+                        index, due_at
+                    )));
+                }
+            }
+            Ok(task)
+        })
+        .collect()
+}
+
+#[derive(Serialize, Default)]
+pub struct RepairReport {
+    // 保留下来的任务数
+    pub kept: usize,
+    // 无法解析为任务而被丢弃的条目数
+    pub dropped: usize,
+    // 补充了 id 或清除了无效截止时间的任务数
+    pub fixed: usize,
+}
+
+// 逐条解析任务数组：丢弃无法解析的条目，为缺少 id 的任务分配新 id，清除无法解析的截止时间
+pub fn repair(tasks: Value) -> Result<(Vec<Task>, RepairReport), AppError> {
+    let Value::Array(entries) = tasks else {
+        return Err(AppError::InvalidInput("Tasks must be an array".to_string()));
+    };
+    let mut report = RepairReport::default();
+    let mut repaired = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let Ok(mut task) = serde_json::from_value::<Task>(entry) else {
+            report.dropped += 1;
+            continue;
+        };
+        let mut fixed = false;
+        if task.id.is_empty() {
+            task.id = uuid::Uuid::new_v4().to_string();
+            fixed = true;
+        }
+        if task.due_at.as_deref().is_some_and(|due_at| crate::timestamp::parse(due_at).is_none()) {
+            task.due_at = None;
+            fixed = true;
+        }
+        report.fixed += usize::from(fixed);
+        repaired.push(task);
+    }
+    report.kept = repaired.len();
+    Ok((repaired, report))
+}
+
+// 标记任务完成并结算计时（与前端完成任务的逻辑一致）；
+// 若为重复任务，在原位置插入下一期的新任务并返回其 id
+pub fn complete(tasks: &mut Vec<Task>, id: &str) -> Result<Option<String>, AppError> {
+    let index = tasks
+        .iter()
+        .position(|task| task.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Task not found: {}", id)))?;
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let task = &mut tasks[index];
+    if task.completed {
+        return Ok(None);
+    }
+    task.completed = true;
+    if task.extra.get("inProgress").and_then(Value::as_bool) == Some(true) {
+        let started = task.extra.get("lastStartAt").and_then(Value::as_i64);
+        let elapsed = task.extra.get("elapsedMs").and_then(Value::as_i64).unwrap_or(0);
+        if let Some(started) = started {
+            task.extra.insert("elapsedMs".into(), (elapsed + now_ms - started).into());
+        }
+    }
+    task.extra.insert("inProgress".into(), false.into());
+    task.extra.insert("lastStartAt".into(), Value::Null);
+
+    let Some(recurrence) = task.recurrence else {
+        return Ok(None);
+    };
+    let base = task
+        .due_at
+        .clone()
+        .unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+    let due_at = next_due(&base, recurrence)
+        .ok_or_else(|| AppError::InvalidInput(format!("Invalid due_at, expected ISO-8601: {}", base)))?;
+
+    let mut next = task.clone();
+    next.id = uuid::Uuid::new_v4().to_string();
+    next.completed = false;
+    next.due_at = Some(due_at);
+    next.extra.insert("createdAt".into(), crate::timestamp::now_iso().into());
+    next.extra.insert("elapsedMs".into(), 0.into());
+
+    let next_id = next.id.clone();
+    tasks.insert(index, next);
+    Ok(Some(next_id))
+}
+
+// bulk_update_tasks 的筛选条件；未设置的条件不参与筛选，全部未设置时匹配所有任务
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct TaskFilter {
+    pub completed: Option<bool>,
+    pub priority: Option<Priority>,
+    // 截止时间早于该时间（ISO-8601，仅有日期时为当天零点）的任务；没有截止时间的任务不匹配
+    pub due_before: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskAction {
+    Complete,
+    Uncomplete,
+    Delete,
+}
+
+impl TaskFilter {
+    fn matches(&self, task: &Task, due_before: Option<DateTime<Utc>>) -> bool {
+        self.completed.is_none_or(|completed| task.completed == completed)
+            && self.priority.is_none_or(|priority| task.priority == priority)
+            && due_before.is_none_or(|due_before| {
+                task.due_at
+                    .as_deref()
+                    .and_then(crate::timestamp::parse)
+                    .is_some_and(|due_at| due_at < due_before)
+            })
+    }
+}
+
+// 对匹配的任务执行同一操作，返回实际发生变化的任务数（已完成的任务再标记完成不计入）；
+// 完成操作与 complete 相同，会结算计时并为重复任务生成下一期
+pub fn bulk_update(
+    tasks: &mut Vec<Task>,
+    filter: &TaskFilter,
+    action: TaskAction,
+) -> Result<usize, AppError> {
+    let due_before = match &filter.due_before {
+        Some(value) => Some(crate::timestamp::parse_start(value).ok_or_else(|| {
+            AppError::InvalidInput(format!("Invalid due_before, expected ISO-8601: {}", value))
+        })?),
+        None => None,
+    };
+    let matched: HashSet<String> = tasks
+        .iter()
+        .filter(|task| filter.matches(task, due_before))
+        .map(|task| task.id.clone())
+        .collect();
+
+    let mut affected = 0;
+    match action {
+        TaskAction::Complete => {
+            for id in &matched {
+                if tasks.iter().any(|task| task.id == *id && !task.completed) {
+                    complete(tasks, id)?;
+                    affected += 1;
+                }
+            }
+        }
+        TaskAction::Uncomplete => {
+            let completed = tasks.iter_mut().filter(|task| task.completed);
+            for task in completed.filter(|task| matched.contains(&task.id)) {
+                task.completed = false;
+                affected += 1;
+            }
+        }
+        TaskAction::Delete => {
+            let before = tasks.len();
+            tasks.retain(|task| !matched.contains(&task.id));
+            affected = before - tasks.len();
+        }
+    }
+    Ok(affected)
+}
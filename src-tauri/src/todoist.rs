@@ -0,0 +1,78 @@
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+use crate::error::AppError;
+use crate::tasks::Task;
+
+// Todoist 导出（同步 API 格式）中的任务项；只读取需要的字段
+#[derive(Deserialize)]
+struct Item {
+    #[serde(default)]
+    content: String,
+    #[serde(default, deserialize_with = "flag")]
+    checked: bool,
+    #[serde(default, deserialize_with = "flag")]
+    is_deleted: bool,
+    #[serde(default, deserialize_with = "flag")]
+    is_archived: bool,
+    #[serde(default)]
+    due: Option<Due>,
+}
+
+#[derive(Deserialize)]
+struct Due {
+    #[serde(default)]
+    date: Option<String>,
+}
+
+// 导出文件可能是 { "items": [...] }，也可能直接是任务项数组
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Export {
+    Sync { items: Vec<Item> },
+    Items(Vec<Item>),
+}
+
+// 旧版导出中的布尔字段以 0/1 表示
+fn flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Bool(value) => value,
+        Value::Number(value) => value.as_i64().is_some_and(|value| value != 0),
+        _ => false,
+    })
+}
+
+// 转换为本地任务：使用新的 id 以免与现有任务冲突，跳过已删除、已归档的任务项；
+// 无法解析的截止时间直接丢弃
+pub fn import(source: &str) -> Result<Vec<Task>, AppError> {
+    let items = match serde_json::from_str(source)
+        .map_err(AppError::serialization("Failed to parse Todoist export"))?
+    {
+        Export::Sync { items } | Export::Items(items) => items,
+    };
+
+    let created_at = crate::timestamp::now_iso();
+    Ok(items
+        .into_iter()
+        .filter(|item| !item.is_deleted && !item.is_archived)
+        .map(|item| {
+            let due_at = item
+                .due
+                .and_then(|due| due.date)
+                .filter(|date| crate::timestamp::parse(date).is_some());
+            let mut task = Task {
+                id: uuid::Uuid::new_v4().to_string(),
+                text: item.content,
+                completed: item.checked,
+                due_at,
+                ..Task::default()
+            };
+            // 与前端新建任务时的初始字段保持一致
+            task.extra.insert("createdAt".into(), created_at.clone().into());
+            task.extra.insert("elapsedMs".into(), 0.into());
+            task.extra.insert("inProgress".into(), false.into());
+            task.extra.insert("lastStartAt".into(), Value::Null);
+            task
+        })
+        .collect())
+}
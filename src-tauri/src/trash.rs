@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{self, PassphraseState};
+use crate::error::AppError;
+use crate::notes::Note;
+
+// 回收站条目保留天数，启动时清理过期条目
+pub const RETENTION_DAYS: i64 = 30;
+
+#[derive(Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub note: Note,
+    pub deleted_at: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct TrashFile {
+    trash: Vec<TrashEntry>,
+}
+
+impl TrashEntry {
+    pub fn new(note: Note) -> Self {
+        TrashEntry {
+            note,
+            deleted_at: crate::timestamp::now_iso(),
+        }
+    }
+}
+
+// trash.json 与 notes.json 一样在启用加密时加密保存
+pub fn load(app_dir: &Path, passphrase: &PassphraseState) -> Result<Vec<TrashEntry>, AppError> {
+    let trash_file = app_dir.join("trash.json");
+    if !trash_file.exists() {
+        return Ok(Vec::new());
+    }
+
+    let data = fs::read(&trash_file).map_err(AppError::io("Failed to load trash"))?;
+    let json = crypto::decode(data, passphrase.get().as_deref())?;
+    serde_json::from_str::<TrashFile>(&json)
+        .map(|file| file.trash)
+        .map_err(AppError::serialization("Failed to parse trash"))
+}
+
+pub fn save(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &PassphraseState,
+    trash: Vec<TrashEntry>,
+) -> Result<(), AppError> {
+    fs::create_dir_all(app_dir).map_err(AppError::io("Failed to create app directory"))?;
+    let json = serde_json::to_string(&TrashFile { trash })
+        .map_err(AppError::serialization("Failed to serialize trash"))?;
+    let contents = crate::encode_notes(app_handle, passphrase, &json)?;
+    crate::write_atomic(&app_dir.join("trash.json"), &contents)
+        .map_err(AppError::io("Failed to save trash"))
+}
+
+// 删除超过 RETENTION_DAYS 的条目并返回这些条目（用于清理附件）；时间戳无法解析的条目视为过期
+pub fn purge_expired(trash: &mut Vec<TrashEntry>) -> Vec<TrashEntry> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(RETENTION_DAYS);
+    let (kept, expired): (Vec<_>, Vec<_>) = std::mem::take(trash).into_iter().partition(|entry| {
+        chrono::DateTime::parse_from_rfc3339(&entry.deleted_at)
+            .map(|deleted_at| deleted_at > cutoff)
+            .unwrap_or(false)
+    });
+    *trash = kept;
+    expired
+}
@@ -0,0 +1,155 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::error::AppError;
+
+// 同步工具通常会连续触发多次文件事件，静默超过该时长后才统一处理
+pub const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(300);
+
+pub const CHANGED_EVENT: &str = "notes-changed-externally";
+
+const WATCHED_FILES: [&str; 2] = ["notes.json", "tasks.json"];
+
+// hashes 记录每个文件最近一次已知的内容（应用自己写入的或已通知过的），内容相同的事件不再通知
+#[derive(Default)]
+pub struct FileWatcher {
+    watcher: Mutex<Option<(RecommendedWatcher, PathBuf)>>,
+    hashes: Mutex<HashMap<PathBuf, u64>>,
+}
+
+// 一次突发的文件事件合并为一个通知；file 为其中第一个文件，files 为全部发生变化的文件
+#[derive(Serialize, Clone)]
+struct ExternalChange {
+    file: String,
+    files: Vec<String>,
+}
+
+fn hash(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+// 应用写入被监听的文件前调用，避免把自己的写入当作外部修改
+pub fn record(app_handle: &tauri::AppHandle, path: &Path, contents: &[u8]) {
+    let state = app_handle.state::<FileWatcher>();
+    state.hashes.lock().unwrap().insert(path.to_path_buf(), hash(contents));
+}
+
+pub fn start(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let (tx, rx) = mpsc::channel();
+    let watcher = notify::recommended_watcher(tx)
+        .map_err(AppError::platform("Failed to create file watcher"))?;
+    *app_handle.state::<FileWatcher>().watcher.lock().unwrap() =
+        Some((watcher, PathBuf::new()));
+
+    let handle = app_handle.clone();
+    std::thread::spawn(move || run(&handle, rx));
+    watch_storage_dir(app_handle)
+}
+
+// 监听当前存储目录；存储目录变更后需重新调用。
+// 监听的是目录而非文件本身，因为原子写入会用新文件替换旧文件
+pub fn watch_storage_dir(app_handle: &tauri::AppHandle) -> Result<(), AppError> {
+    let dir = crate::storage_dir(app_handle)?;
+    let state = app_handle.state::<FileWatcher>();
+    let mut guard = state.watcher.lock().unwrap();
+    let Some((watcher, watched)) = guard.as_mut() else {
+        return Ok(());
+    };
+    if *watched == dir {
+        return Ok(());
+    }
+
+    if !watched.as_os_str().is_empty() {
+        let _ = watcher.unwatch(watched);
+    }
+    fs::create_dir_all(&dir).map_err(AppError::io("Failed to create app directory"))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(AppError::platform("Failed to watch storage directory"))?;
+    *watched = dir.clone();
+
+    // 以当前内容为基准，只通知之后发生的修改
+    let mut hashes = state.hashes.lock().unwrap();
+    for name in WATCHED_FILES {
+        let path = dir.join(name);
+        if let Ok(contents) = fs::read(&path) {
+            hashes.insert(path, hash(&contents));
+        }
+    }
+    Ok(())
+}
+
+fn run(app_handle: &tauri::AppHandle, rx: Receiver<notify::Result<notify::Event>>) {
+    while let Ok(event) = rx.recv() {
+        let mut changed = BTreeSet::new();
+        collect(&mut changed, event);
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE_INTERVAL) {
+            collect(&mut changed, event);
+        }
+        let files: Vec<String> = changed
+            .iter()
+            .filter_map(|path| check(app_handle, path))
+            .collect();
+        if !files.is_empty() {
+            emit(app_handle, files);
+        }
+    }
+}
+
+fn collect(changed: &mut BTreeSet<PathBuf>, event: notify::Result<notify::Event>) {
+    let event = match event {
+        Ok(event) => event,
+        Err(e) => {
+            tracing::warn!("File watcher error: {}", e);
+            return;
+        }
+    };
+    if event.kind.is_access() {
+        return;
+    }
+    changed.extend(event.paths.into_iter().filter(|path| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| WATCHED_FILES.contains(&name))
+    }));
+}
+
+// 内容与最近一次已知内容（含应用自己写入的内容）不同才视为外部修改，返回文件名；
+// 被删除或暂时无法读取的文件忽略
+fn check(app_handle: &tauri::AppHandle, path: &Path) -> Option<String> {
+    let contents = fs::read(path).ok()?;
+    let current = hash(&contents);
+    let state = app_handle.state::<FileWatcher>();
+    let previous = state.hashes.lock().unwrap().insert(path.to_path_buf(), current);
+    if previous == Some(current) {
+        return None;
+    }
+    path.file_name().map(|name| name.to_string_lossy().into_owned())
+}
+
+fn emit(app_handle: &tauri::AppHandle, files: Vec<String>) {
+    let change = ExternalChange {
+        file: files.first().cloned().unwrap_or_default(),
+        files,
+    };
+    if let Err(e) = app_handle.emit(CHANGED_EVENT, change) {
+        tracing::warn!("Failed to emit {}: {}", CHANGED_EVENT, e);
+    }
+}
+
+// 数据在前端之外被修改（外部编辑、本地 HTTP API 等）时通知前端重新加载
+pub fn notify_changed(app_handle: &tauri::AppHandle, file: &str) {
+    emit(app_handle, vec![file.to_string()]);
+}
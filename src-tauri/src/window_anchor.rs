@@ -0,0 +1,94 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Monitor, PhysicalPosition, Window};
+
+use crate::error::AppError;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    pub fn parse(value: &str) -> Result<Self, AppError> {
+        match value {
+            "top_left" => Ok(Corner::TopLeft),
+            "top_right" => Ok(Corner::TopRight),
+            "bottom_left" => Ok(Corner::BottomLeft),
+            "bottom_right" => Ok(Corner::BottomRight),
+            _ => Err(AppError::InvalidInput(format!(
+                "Unknown corner: {}, expected top_left, top_right, bottom_left or bottom_right",
+                value
+            ))),
+        }
+    }
+}
+
+// 与工作区边缘保持的距离（逻辑像素）
+const MARGIN: f64 = 16.0;
+
+// 最近一次贴靠时所在的显示器，用于判断窗口是否被移到了另一个显示器
+static LAST_MONITOR: Mutex<Option<String>> = Mutex::new(None);
+
+fn monitor_key(monitor: &Monitor) -> String {
+    monitor
+        .name()
+        .cloned()
+        .unwrap_or_else(|| format!("{:?}", monitor.position()))
+}
+
+// 把窗口贴到当前显示器工作区（不含任务栏、Dock）的指定角落
+pub fn apply(window: &Window, corner: Corner) -> Result<(), AppError> {
+    let monitor = window
+        .current_monitor()
+        .map_err(AppError::platform("Failed to get current monitor"))?;
+    let Some(monitor) = monitor else {
+        return Ok(());
+    };
+    let size = window
+        .outer_size()
+        .map_err(AppError::platform("Failed to get window size"))?;
+
+    let area = monitor.work_area();
+    let margin = (MARGIN * monitor.scale_factor()).round() as i32;
+    let left = area.position.x + margin;
+    let top = area.position.y + margin;
+    // 窗口比工作区还大时贴左上角，保证标题栏可见
+    let right = area.position.x + area.size.width as i32 - size.width as i32 - margin;
+    let bottom = area.position.y + area.size.height as i32 - size.height as i32 - margin;
+    let (right, bottom) = (right.max(left), bottom.max(top));
+    let (x, y) = match corner {
+        Corner::TopLeft => (left, top),
+        Corner::TopRight => (right, top),
+        Corner::BottomLeft => (left, bottom),
+        Corner::BottomRight => (right, bottom),
+    };
+
+    *LAST_MONITOR.lock().unwrap() = Some(monitor_key(&monitor));
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(AppError::platform("Failed to set window position"))
+}
+
+// 窗口移动后调用：设置了贴靠角且窗口已到了另一个显示器（拖动或显示器变化）时，
+// 重新贴到新显示器的对应角落；同一显示器内的拖动不做处理
+pub fn on_moved(window: &Window) -> Result<(), AppError> {
+    let Some(corner) = crate::config::load(window.app_handle()).window_anchor else {
+        return Ok(());
+    };
+    let monitor = window
+        .current_monitor()
+        .map_err(AppError::platform("Failed to get current monitor"))?;
+    let Some(monitor) = monitor else {
+        return Ok(());
+    };
+    if LAST_MONITOR.lock().unwrap().as_deref() == Some(monitor_key(&monitor).as_str()) {
+        return Ok(());
+    }
+    apply(window, corner)
+}
@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, PhysicalPosition, PhysicalSize, WebviewWindow, Window};
+
+use crate::error::AppError;
+
+// 窗口位置与内部尺寸（物理像素），保存在 app_data_dir/window_state.json
+#[derive(Serialize, Deserialize)]
+struct WindowState {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+fn state_file(app_handle: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    Ok(crate::app_data_dir(app_handle)?.join("window_state.json"))
+}
+
+pub fn save(window: &Window) -> Result<(), AppError> {
+    // 最小化时的坐标没有意义（Windows 上为 -32000）
+    if window.is_minimized().unwrap_or(false) {
+        return Ok(());
+    }
+
+    let position = window
+        .outer_position()
+        .map_err(AppError::platform("Failed to get window position"))?;
+    let size = window
+        .inner_size()
+        .map_err(AppError::platform("Failed to get window size"))?;
+    let state = WindowState {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+
+    let app_dir = crate::app_data_dir(window.app_handle())?;
+    fs::create_dir_all(&app_dir).map_err(AppError::io("Failed to create app directory"))?;
+    let contents = serde_json::to_string(&state)
+        .map_err(AppError::serialization("Failed to serialize window state"))?;
+    crate::write_atomic(&state_file(window.app_handle())?, contents.as_bytes())
+        .map_err(AppError::io("Failed to save window state"))
+}
+
+// 启动时恢复窗口位置与尺寸；若保存的位置已不在任何显示器上（如拔掉了外接显示器），则拉回主显示器内
+pub fn restore(window: &WebviewWindow) -> Result<(), AppError> {
+    let state: WindowState = match fs::read_to_string(state_file(window.app_handle())?)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+    {
+        Some(state) => state,
+        None => return Ok(()),
+    };
+
+    window
+        .set_size(PhysicalSize::new(state.width, state.height))
+        .map_err(AppError::platform("Failed to restore window size"))?;
+
+    let monitors = window
+        .available_monitors()
+        .map_err(AppError::platform("Failed to list monitors"))?;
+    // 窗口顶部需落在某个显示器内，否则无法拖动
+    let on_screen = monitors.iter().any(|monitor| {
+        let origin = monitor.position();
+        let size = monitor.size();
+        state.x + state.width as i32 > origin.x
+            && state.x < origin.x + size.width as i32
+            && state.y >= origin.y
+            && state.y < origin.y + size.height as i32
+    });
+
+    let position = if on_screen {
+        PhysicalPosition::new(state.x, state.y)
+    } else {
+        let monitor = window
+            .primary_monitor()
+            .map_err(AppError::platform("Failed to get primary monitor"))?
+            .or_else(|| monitors.into_iter().next());
+        let Some(monitor) = monitor else {
+            return Ok(());
+        };
+        let origin = monitor.position();
+        let size = monitor.size();
+        let max_x = origin.x + (size.width as i32 - state.width as i32).max(0);
+        let max_y = origin.y + (size.height as i32 - state.height as i32).max(0);
+        PhysicalPosition::new(state.x.clamp(origin.x, max_x), state.y.clamp(origin.y, max_y))
+    };
+
+    window
+        .set_position(position)
+        .map_err(AppError::platform("Failed to restore window position"))
+}
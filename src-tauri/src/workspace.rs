@@ -0,0 +1,175 @@
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::attachments;
+use crate::backup;
+use crate::config::{self, AppConfig};
+use crate::crypto::PassphraseState;
+use crate::error::AppError;
+
+// 整个工作区的 zip 归档：notes.json、tasks.json、config.json、backups/ 下的备份
+// 与 attachments/ 下笔记附带的图片
+const NOTES_ENTRY: &str = "notes.json";
+const TASKS_ENTRY: &str = "tasks.json";
+const CONFIG_ENTRY: &str = "config.json";
+const BACKUPS_PREFIX: &str = "backups/";
+
+fn add(zip: &mut ZipWriter<File>, name: &str, contents: &[u8]) -> Result<(), AppError> {
+    zip.start_file(name, SimpleFileOptions::default())
+        .map_err(AppError::serialization("Failed to write archive"))?;
+    zip.write_all(contents).map_err(AppError::io("Failed to write archive"))
+}
+
+// JSON 后端直接归档磁盘上的文件（加密时保持加密），其它后端导出为当前版本的 notes.json 格式
+pub fn notes_contents(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    passphrase: &PassphraseState,
+) -> Result<Vec<u8>, AppError> {
+    let notes_file = app_dir.join(NOTES_ENTRY);
+    let backend = config::load(app_handle).storage_backend;
+    if backend == config::StorageBackend::Json && notes_file.exists() {
+        return fs::read(&notes_file).map_err(AppError::io("Failed to load notes"));
+    }
+    let notes = crate::read_stored_notes_json(app_handle, app_dir, passphrase)?;
+    Ok(crate::notes::to_envelope(&notes, false)?.into_bytes())
+}
+
+pub fn export(
+    app_handle: &tauri::AppHandle,
+    passphrase: &PassphraseState,
+    dest: &Path,
+) -> Result<(), AppError> {
+    let app_dir = crate::storage_dir(app_handle)?;
+    let notes = notes_contents(app_handle, &app_dir, passphrase)?;
+    let tasks = crate::read_tasks_json(app_handle, &app_dir)?;
+
+    let file = File::create(dest).map_err(AppError::io("Failed to create archive"))?;
+    let mut zip = ZipWriter::new(file);
+    add(&mut zip, NOTES_ENTRY, &notes)?;
+    add(&mut zip, TASKS_ENTRY, tasks.as_bytes())?;
+    if let Ok(config) = fs::read(crate::app_data_dir(app_handle)?.join(CONFIG_ENTRY)) {
+        add(&mut zip, CONFIG_ENTRY, &config)?;
+    }
+    for entry in backup::list(&app_dir).map_err(AppError::io("Failed to list backups"))? {
+        let data =
+            backup::read_raw(&app_dir, &entry).map_err(AppError::io("Failed to read backup"))?;
+        add(&mut zip, &format!("{}{}", BACKUPS_PREFIX, entry.filename), &data)?;
+    }
+    for path in attachments::list(&app_dir)? {
+        let data =
+            fs::read(app_dir.join(&path)).map_err(AppError::io("Failed to read attachment"))?;
+        add(&mut zip, &path, &data)?;
+    }
+    zip.finish().map_err(AppError::serialization("Failed to write archive"))?;
+    Ok(())
+}
+
+fn invalid(e: zip::result::ZipError) -> AppError {
+    AppError::InvalidInput(format!("Invalid archive: {}", e))
+}
+
+// 从归档恢复：先校验结构与每个 JSON 文件都能解析，再把当前数据整体导出到 backups/pre-import-<毫秒>.zip，
+// 最后覆盖笔记、任务与配置并补充归档中本机没有的备份与附件。
+// 配置中的存储目录、存储后端与加密开关属于本机设置，保持不变
+pub fn import(
+    app_handle: &tauri::AppHandle,
+    passphrase: &PassphraseState,
+    src: &Path,
+) -> Result<(), AppError> {
+    let file = File::open(src).map_err(AppError::io("Failed to open archive"))?;
+    let mut zip = ZipArchive::new(file).map_err(invalid)?;
+
+    let (mut notes, mut tasks, mut imported_config) = (None, None, None);
+    let (mut backups, mut images) = (Vec::new(), Vec::new());
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(invalid)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .map_err(AppError::io("Failed to read archive"))?;
+        match name.as_str() {
+            NOTES_ENTRY => notes = Some(data),
+            TASKS_ENTRY => tasks = Some(data),
+            CONFIG_ENTRY => imported_config = Some(data),
+            // 附件只接受 attachments/<id>/<文件名> 形式的路径
+            _ if attachments::is_attachment_path(&name) => images.push((name.clone(), data)),
+            // 只接受 backups/ 下直接存放、名称合法的备份，文件名不会包含路径分隔符
+            _ => match name.strip_prefix(BACKUPS_PREFIX) {
+                Some(filename) if backup::is_backup_name(filename) => {
+                    backups.push((filename.to_string(), data));
+                }
+                _ => {
+                    return Err(AppError::InvalidInput(format!(
+                        "Unexpected entry in archive: {}",
+                        name
+                    )))
+                }
+            },
+        }
+    }
+
+    let notes = notes
+        .ok_or_else(|| AppError::InvalidInput("Archive is missing notes.json".to_string()))?;
+    let notes = crate::crypto::decode(notes, passphrase.get().as_deref())?;
+    let notes = crate::notes::from_envelope(&notes)?;
+    let tasks = tasks
+        .ok_or_else(|| AppError::InvalidInput("Archive is missing tasks.json".to_string()))?;
+    let tasks = String::from_utf8(tasks)
+        .map_err(AppError::serialization("Failed to parse tasks"))?;
+    crate::tasks::parse(&tasks)?;
+    let tasks: serde_json::Value =
+        serde_json::from_str(&tasks).map_err(AppError::serialization("Failed to parse tasks"))?;
+    let imported_config: Option<AppConfig> = imported_config
+        .map(|data| serde_json::from_slice(&data))
+        .transpose()
+        .map_err(AppError::serialization("Failed to parse config"))?;
+
+    let app_dir = crate::storage_dir(app_handle)?;
+    let backups_dir = backup::backups_dir(&app_dir);
+    fs::create_dir_all(&backups_dir).map_err(AppError::io("Failed to create backups directory"))?;
+    export(
+        app_handle,
+        passphrase,
+        &backups_dir.join(format!("pre-import-{}.zip", crate::now_millis())),
+    )?;
+
+    // 归档中的笔记为存储形式（secure 笔记的正文是密文），直接写入后端，
+    // 经过 write_notes_json 会被再次加密或因未解锁而失败
+    crate::write_notes_json_to_backend(app_handle, &app_dir, passphrase, &notes)?;
+    crate::record_stored_history(app_handle, passphrase, &notes);
+    crate::write_tasks_json(app_handle, &app_dir, &tasks)?;
+    if let Some(mut imported) = imported_config {
+        let current = config::load(app_handle);
+        imported.storage_path = current.storage_path;
+        imported.storage_backend = current.storage_backend;
+        imported.encryption_enabled = current.encryption_enabled;
+        config::save(app_handle, &imported)?;
+    }
+    for (filename, data) in backups {
+        let path = backups_dir.join(&filename);
+        if !path.exists() {
+            crate::write_atomic(&path, &data).map_err(AppError::io("Failed to restore backup"))?;
+        }
+    }
+    // 附件文件名为随机 uuid，本机已有同名文件时即为同一张图片
+    for (name, data) in images {
+        let path = app_dir.join(&name);
+        if path.exists() {
+            continue;
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(AppError::io("Failed to create attachment directory"))?;
+        }
+        crate::write_atomic(&path, &data).map_err(AppError::io("Failed to restore attachment"))?;
+    }
+    Ok(())
+}
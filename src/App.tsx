@@ -8,6 +8,9 @@ declare global {
       core: {
         invoke: (cmd: string, args?: any) => Promise<any>;
       };
+      event: {
+        listen: (event: string, handler: (event: any) => void) => Promise<() => void>;
+      };
     };
   }
 }
@@ -33,6 +36,8 @@ function App() {
   const [showNewTask, setShowNewTask] = useState(false);
   const [newTaskText, setNewTaskText] = useState("");
   const newTaskInputRef = useRef<HTMLInputElement>(null);
+  // 最新的任务列表，供关闭前的最后一次保存使用
+  const tasksRef = useRef<Task[]>([]);
 
   // 保持已完成任务在列表底部的排序
   const orderTasks = (list: Task[]): Task[] => {
@@ -46,6 +51,22 @@ function App() {
     loadTasks();
   }, []);
 
+  useEffect(() => {
+    tasksRef.current = tasks;
+  }, [tasks]);
+
+  // 窗口关闭或退出前后端会发出 flush-before-close：完成最后一次保存后确认
+  useEffect(() => {
+    if (!window.__TAURI__?.event?.listen) return;
+    const unlisten = window.__TAURI__.event.listen("flush-before-close", async () => {
+      await saveTasks(tasksRef.current);
+      await window.__TAURI__.core.invoke("close_ready");
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
   // 新任务弹窗打开时自动聚焦输入
   useEffect(() => {
     if (showNewTask) {
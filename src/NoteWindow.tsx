@@ -0,0 +1,81 @@
+import { useState, useEffect, useRef } from "react";
+import "./App.css";
+
+interface Note {
+  id: string;
+  title: string;
+  body: string;
+  color?: string;
+  [key: string]: any;
+}
+
+// 弹出的单条笔记窗口：只显示并编辑一条笔记，失焦时保存
+function NoteWindow({ noteId }: { noteId: string }) {
+  const [note, setNote] = useState<Note | null>(null);
+  const [missing, setMissing] = useState(false);
+  const dirtyRef = useRef(false);
+
+  useEffect(() => {
+    loadNote();
+  }, [noteId]);
+
+  const loadNote = async () => {
+    try {
+      const notesJson = await window.__TAURI__.core.invoke("load_notes");
+      const notes: Note[] = JSON.parse(notesJson || "[]");
+      const found = notes.find(n => n.id === noteId);
+      if (found) {
+        setNote(found);
+      } else {
+        setMissing(true);
+      }
+    } catch (error) {
+      console.error("Failed to load note:", error);
+    }
+  };
+
+  const saveNote = async () => {
+    if (!note || !dirtyRef.current) return;
+    dirtyRef.current = false;
+    try {
+      await window.__TAURI__.core.invoke("save_note", {
+        note: { ...note, updated_at: new Date().toISOString() },
+      });
+    } catch (error) {
+      console.error("Failed to save note:", error);
+    }
+  };
+
+  const update = (changes: Partial<Note>) => {
+    if (!note) return;
+    dirtyRef.current = true;
+    setNote({ ...note, ...changes });
+  };
+
+  if (missing) {
+    return <div className="note-window"><div className="empty-state">笔记不存在</div></div>;
+  }
+  if (!note) {
+    return <div className="note-window" />;
+  }
+
+  return (
+    <div className="note-window" style={{ background: note.color }} data-tauri-drag-region>
+      <input
+        className="note-window-title"
+        value={note.title}
+        placeholder="标题"
+        onChange={(e) => update({ title: e.target.value })}
+        onBlur={saveNote}
+      />
+      <textarea
+        className="note-window-body"
+        value={note.body}
+        onChange={(e) => update({ body: e.target.value })}
+        onBlur={saveNote}
+      />
+    </div>
+  );
+}
+
+export default NoteWindow;
@@ -1,9 +1,13 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import NoteWindow from "./NoteWindow";
+
+// 弹出的笔记窗口加载 index.html?note=<id>
+const noteId = new URLSearchParams(window.location.search).get("note");
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {noteId ? <NoteWindow noteId={noteId} /> : <App />}
   </React.StrictMode>,
 );